@@ -0,0 +1,6068 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::fs::read_to_string;
+use std::io::{self, Write};
+use std::ops::{Add, Mul, Neg, Sub};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Interpreter that Rust programs embed to run Lazo scripts
+pub struct Interpreter {
+    scope: Scope,
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    /// Interpreter with the standard library
+    pub fn new() -> Interpreter {
+        Interpreter { scope: stdlib() }
+    }
+
+    /// Raise type error instead of converting values of wrong type
+    pub fn set_strict(&mut self, strict: bool) {
+        self.scope.runtime().strict.set(strict);
+    }
+
+    /// Run the code and get the value of its last line
+    pub fn eval_str(&mut self, code: &str) -> Result<Type, LazoError> {
+        self.eval_source("<string>", code)
+    }
+
+    /// Run the code that's named to report where errors come from
+    pub fn eval_source(&mut self, name: &str, code: &str) -> Result<Type, LazoError> {
+        self.scope.runtime().start();
+        run(
+            Span::new(name.to_string(), code.to_string()),
+            &mut self.scope,
+        )
+    }
+
+    /// Run the script, modules that it imports are found from its directory
+    pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<Type, LazoError> {
+        self.scope.runtime().start();
+        run_file(path.as_ref(), &mut self.scope)
+    }
+
+    /// Call the function that's defined in the interpreter
+    pub fn call(&mut self, name: &str, args: Vec<Type>) -> Result<Type, LazoError> {
+        let mut expr = vec![Type::Symbol(name.to_string())];
+        expr.extend(args.iter().map(Type::quote));
+        self.scope.runtime().start();
+        Type::Expr(expr, None).eval(&mut self.scope)
+    }
+
+    /// Value of the variable that's defined at the top level of the interpreter
+    pub fn get(&self, name: &str) -> Option<Type> {
+        self.scope.get(name)
+    }
+
+    /// Choose the engine to run the code, the VM is used by default
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.scope.runtime().engine.set(engine);
+    }
+
+    /// Budget of the execution, that's counted from each time the interpreter runs the code
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.scope.runtime().limits.set(limits);
+    }
+
+    /// Forbid the program to use the builtins that need the capability
+    pub fn deny(&mut self, capability: Capability) {
+        self.scope.runtime().denied.borrow_mut().insert(capability);
+    }
+
+    /// Define the variable from the Rust value, that the code can use by the name
+    pub fn define(&mut self, name: &str, value: impl Into<Type>) {
+        self.scope.insert(name.to_string(), value.into());
+    }
+
+    /// Register the Rust closure as the function, that's called with the evaluated arguments.
+    /// It can capture the state of the host like handles and counters
+    pub fn register(
+        &mut self,
+        name: &str,
+        arity: Arity,
+        doc: &str,
+        func: impl Fn(Vec<Type>) -> Result<Type, LazoError> + 'static,
+    ) {
+        let (name, value) = builtin(name, arity, doc, move |params, scope| {
+            let mut args = vec![];
+            for arg in params {
+                args.push(arg.eval(scope)?);
+            }
+            func(args)
+        });
+        self.scope.insert(name, value);
+    }
+}
+
+/// Run the code and get the value of its last line
+fn run(code: Span, scope: &mut Scope) -> Result<Type, LazoError> {
+    let mut program = vec![];
+    for line in tokenize(code)? {
+        program.push(parse(line)?);
+    }
+
+    // Type annotations are checked before the execution
+    Checker::check(&program, scope)?;
+    let mut result = Type::Null;
+    for line in program {
+        result = match scope.runtime().engine.get() {
+            // Each line is compiled after the previous ones run, to know macros that they define
+            Engine::Vm => Machine::eval(Compiler::expression(&line, scope), scope)?,
+            Engine::TreeWalker => line.eval(scope)?,
+        };
+    }
+    Ok(result)
+}
+
+/// Run the script as the main module, modules that it imports are found from its directory
+fn run_file(path: &Path, scope: &mut Scope) -> Result<Type, LazoError> {
+    let code = read_to_string(path).map_err(|err| {
+        LazoError::Io(format!("opening file `{}` is fault: {err}", path.display()))
+    })?;
+    let file = path.canonicalize().unwrap_or(path.to_path_buf());
+    scope.0.borrow_mut().file = Some(file.clone());
+    let runtime = scope.runtime();
+    runtime.importing.borrow_mut().push(file);
+    let result = run(Span::new(path.display().to_string(), code), scope);
+    runtime.importing.borrow_mut().pop();
+    result
+}
+
+/// Find the module in the directory of the script, then the ones in `LAZO_PATH`
+fn resolve(target: &Type, scope: &Scope) -> Result<PathBuf, LazoError> {
+    let name = match target {
+        Type::String(path) => PathBuf::from(path),
+        Type::Symbol(name) => PathBuf::from(format!("{name}.lz")),
+        other => {
+            return Err(LazoError::Syntax(format!(
+            "module should be specified by the name or the path, but provided `{other:?}` is not"
+        )))
+        }
+    };
+    let mut dirs = vec![scope
+        .file()
+        .and_then(|file| file.parent().map(Path::to_path_buf))
+        .unwrap_or_default()];
+    if let Some(paths) = std::env::var_os("LAZO_PATH") {
+        dirs.extend(std::env::split_paths(&paths));
+    }
+    dirs.iter()
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+        .ok_or(LazoError::Runtime(format!(
+            "module `{}` is not found",
+            name.display()
+        )))
+}
+
+/// Evaluate the module in its own namespace and get the values that it exports,
+/// each module is evaluated only once and shared by the scripts that import it
+fn load(path: PathBuf, scope: &Scope) -> Result<HashMap<String, Type>, LazoError> {
+    let runtime = scope.runtime();
+    if let Some(exports) = runtime.modules.borrow().get(&path) {
+        return Ok(exports.clone());
+    }
+    if runtime.importing.borrow().contains(&path) {
+        let importing = runtime.importing.borrow();
+        let cycle: Vec<String> = importing
+            .iter()
+            .skip_while(|i| **i != path)
+            .chain([&path])
+            .map(|i| i.display().to_string())
+            .collect();
+        return Err(LazoError::Runtime(format!(
+            "circular import is found: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    let code = read_to_string(&path).map_err(|err| {
+        LazoError::Io(format!("opening file `{}` is fault: {err}", path.display()))
+    })?;
+    let mut module = scope.module(path.clone());
+    runtime.importing.borrow_mut().push(path.clone());
+    let result = run(Span::new(path.display().to_string(), code), &mut module);
+    runtime.importing.borrow_mut().pop();
+    result?;
+
+    // All the definitions are exported unless it's specified by `export`
+    let env = module.0.borrow();
+    let exports = match &env.exports {
+        Some(names) => {
+            let mut exports = HashMap::new();
+            for name in names {
+                let Some(value) = env.vars.get(name) else {
+                    return Err(LazoError::Runtime(format!(
+                        "`{name}` is exported by `{}` but it's not defined",
+                        path.display()
+                    )));
+                };
+                exports.insert(name.to_owned(), value.clone());
+            }
+            exports
+        }
+        None => env.vars.clone(),
+    };
+    runtime.modules.borrow_mut().insert(path, exports.clone());
+    Ok(exports)
+}
+
+/// Levenshtein distance, how many characters should be edited to be the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + (x != *y) as usize)
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Pairs of the name and the value in binding list like `((x 1) (y 2))`
+fn bindings(list: &Type) -> Result<Vec<(String, Type)>, LazoError> {
+    let mut result = vec![];
+    for binding in list.get_list() {
+        match binding.get_list().as_slice() {
+            [Type::Symbol(name), value] => result.push((name.to_owned(), value.to_owned())),
+            _ => {
+                return Err(LazoError::Syntax(format!(
+                    "binding should be pair of the name and the value, but provided `{binding:?}` is not"
+                )))
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Parameter of the function and its type annotation
+type Param = (Type, Option<String>);
+
+/// Parameters and the return type of the function
+type Signature = (Vec<Param>, Option<String>);
+
+/// Parameters with their type annotations like `(x : number)`,
+/// the return type that's given after `->` and the body of the function
+fn signature<'a>(args: &[Type], body: &'a [Type]) -> Result<(Signature, &'a [Type]), LazoError> {
+    let mut params = vec![];
+    for arg in args {
+        params.push(annotation(arg)?);
+    }
+    match body {
+        [Type::Symbol(arrow), Type::Symbol(name), body @ ..] if arrow == "->" => {
+            Ok(((params, Some(type_name(name)?)), body))
+        }
+        body => Ok(((params, None), body)),
+    }
+}
+
+fn annotation(arg: &Type) -> Result<Param, LazoError> {
+    match arg {
+        Type::Expr(list, _) => match list.as_slice() {
+            [arg, Type::Symbol(colon), Type::Symbol(name)] if colon == ":" => {
+                pattern_vars(arg)?;
+                Ok((arg.clone(), Some(type_name(name)?)))
+            }
+            _ => Err(LazoError::Syntax(format!(
+                "annotated parameter should be like `(x : number)`, but provided `{arg:?}` is not"
+            ))),
+        },
+        // List parameter destructures the argument
+        arg @ Type::List(_) => {
+            pattern_vars(arg)?;
+            Ok((arg.clone(), None))
+        }
+        arg => Ok((arg.clone(), None)),
+    }
+}
+
+/// Variables that the pattern binds in order, it's syntax error if it isn't pattern.
+/// Patterns are `_`, variables, literals, `'symbol`, `(pattern : type)` and lists like `[x y . rest]`
+fn pattern_vars(pattern: &Type) -> Result<Vec<String>, LazoError> {
+    match pattern {
+        Type::Symbol(name) if name == "_" => Ok(vec![]),
+        Type::Symbol(name) if name != "." => Ok(vec![name.to_owned()]),
+        Type::Number(_) | Type::String(_) | Type::Bool(_) | Type::Null => Ok(vec![]),
+        Type::List(items) => {
+            let (items, rest) = split_rest(items);
+            let mut vars = vec![];
+            for item in items.iter().chain(rest) {
+                vars.extend(pattern_vars(item)?);
+            }
+            Ok(vars)
+        }
+        Type::Expr(list, _) => match list.as_slice() {
+            [Type::Symbol(quote), _] if quote == "quote" => Ok(vec![]),
+            [inner, Type::Symbol(colon), Type::Symbol(name)] if colon == ":" => {
+                type_name(name)?;
+                pattern_vars(inner)
+            }
+            _ => Err(LazoError::Syntax(format!("`{pattern:?}` is not pattern"))),
+        },
+        _ => Err(LazoError::Syntax(format!("`{pattern:?}` is not pattern"))),
+    }
+}
+
+/// Patterns of the elements and the rest after `.`
+fn split_rest(items: &[Type]) -> (&[Type], Option<&Type>) {
+    match items {
+        [items @ .., Type::Symbol(dot), rest] if dot == "." => (items, Some(rest)),
+        items => (items, None),
+    }
+}
+
+/// Bind the variables of the pattern to the parts of the value, whether it matches
+fn destructure(
+    pattern: &Type,
+    value: Type,
+    binds: &mut Vec<(String, Type)>,
+) -> Result<bool, LazoError> {
+    match pattern {
+        Type::Symbol(name) if name == "_" => Ok(true),
+        // Variable gets the value as it is, so it's not forced yet
+        Type::Symbol(name) => {
+            binds.push((name.to_owned(), value));
+            Ok(true)
+        }
+        Type::List(items) => {
+            let (items, rest) = split_rest(items);
+            destructure_seq(items, rest, value.forced()?, binds)
+        }
+        Type::Expr(list, _) => match list.as_slice() {
+            [Type::Symbol(quote), datum] if quote == "quote" => Ok(value.forced()? == *datum),
+            [inner, Type::Symbol(colon), Type::Symbol(name)] if colon == ":" => {
+                let value = value.forced()?;
+                Ok(value.has_type(name) && destructure(inner, value, binds)?)
+            }
+            _ => Err(LazoError::Syntax(format!("`{pattern:?}` is not pattern"))),
+        },
+        literal => Ok(value.forced()? == *literal),
+    }
+}
+
+/// Elements of the list or the stream are matched in order, the stream is read only as needed
+fn destructure_seq(
+    items: &[Type],
+    rest: Option<&Type>,
+    value: Type,
+    binds: &mut Vec<(String, Type)>,
+) -> Result<bool, LazoError> {
+    if let Some(mut stream) = value.stream() {
+        for item in items {
+            let Some((first, next)) = stream.next()? else {
+                return Ok(false);
+            };
+            if !destructure(item, first, binds)? {
+                return Ok(false);
+            }
+            stream = next;
+        }
+        return match rest {
+            Some(rest) => destructure(rest, Type::Stream(stream), binds),
+            None => Ok(stream.next()?.is_none()),
+        };
+    }
+    let (Type::List(list) | Type::Expr(list, _)) = value else {
+        return Ok(false);
+    };
+    if list.len() < items.len() || (rest.is_none() && list.len() != items.len()) {
+        return Ok(false);
+    }
+    for (item, value) in items.iter().zip(&list) {
+        if !destructure(item, value.clone(), binds)? {
+            return Ok(false);
+        }
+    }
+    match rest {
+        Some(rest) => destructure(rest, Type::List(list[items.len()..].to_vec()), binds),
+        None => Ok(true),
+    }
+}
+
+/// Define the variables of the pattern in the scope, it's error if the value doesn't match
+fn bind_pattern(pattern: &Type, value: Type, scope: &Scope) -> Result<(), LazoError> {
+    let mut binds = vec![];
+    if !destructure(pattern, value.clone(), &mut binds)? {
+        return Err(LazoError::Match(value.forced()?));
+    }
+    for (name, value) in binds {
+        scope.insert(name, value);
+    }
+    Ok(())
+}
+
+/// Pattern, guard and body of the clause of `match`, like `([x . rest] when (> x 0) rest)`
+fn clause(clause: &Type) -> Result<(&Type, Option<&Type>, &[Type]), LazoError> {
+    let (Type::Expr(list, _) | Type::List(list)) = clause else {
+        return Err(LazoError::Syntax(format!(
+            "clause of match should be like `(pattern body)`, but provided `{clause:?}` is not"
+        )));
+    };
+    let (pattern, guard, body) = match list.as_slice() {
+        [pattern, Type::Symbol(when), guard, body @ ..] if when == "when" => {
+            (pattern, Some(guard), body)
+        }
+        [pattern, body @ ..] => (pattern, None, body),
+        [] => (&Type::Null, None, &[][..]),
+    };
+    if body.is_empty() {
+        return Err(LazoError::Syntax(format!(
+            "clause of match should be like `(pattern body)`, but provided `{clause:?}` is not"
+        )));
+    }
+    pattern_vars(pattern)?;
+    Ok((pattern, guard, body))
+}
+
+/// Names of the builtin types that can be used in type annotations, `any` accepts every type
+const TYPE_NAMES: [&str; 13] = [
+    "any", "number", "string", "bool", "list", "stream", "map", "expr", "symbol", "function",
+    "thunk", "error", "null",
+];
+
+/// Name of the type in the annotation, other names than the builtin ones are the record types
+/// that're checked by the record's tag. The checker knows which records are defined
+fn type_name(name: &str) -> Result<String, LazoError> {
+    if TYPE_NAMES.contains(&name) || name.starts_with(char::is_alphabetic) {
+        Ok(name.to_string())
+    } else {
+        Err(LazoError::Syntax(format!("unknown type name `{name}`")))
+    }
+}
+
+/// Builtin `the` that checks the type of the value at runtime like `(the number x)`,
+/// it's also inserted where untyped code passes the value to typed function
+fn the(params: Vec<Type>, scope: &mut Scope) -> Result<Type, LazoError> {
+    let annotation = type_name(&params[0].get_string())?;
+    let value = params[1].eval(scope)?;
+    if value.has_type(&annotation) {
+        Ok(value)
+    } else {
+        Err(LazoError::Type(format!(
+            "expected {annotation}, got {} `{value:?}`",
+            value.get_type()
+        )))
+    }
+}
+
+thread_local! {
+    /// `the` that checks typed arguments, it's not affected by the user's definition
+    static THE: Type = builtin("the", Arity::Exact(2), "Value that's checked to be the type", the).1;
+}
+
+/// Pair of the name and the builtin, to define it in the scope
+fn builtin(
+    name: &str,
+    arity: Arity,
+    doc: &str,
+    func: impl Fn(Vec<Type>, &mut Scope) -> Result<Type, LazoError> + 'static,
+) -> (String, Type) {
+    let func = BuiltIn {
+        name: name.to_string(),
+        arity,
+        doc: doc.to_string(),
+        func: Box::new(func),
+    };
+    (
+        name.to_string(),
+        Type::Function(Function::BuiltIn(Rc::new(func))),
+    )
+}
+
+/// Builtins that have no side effect and finish soon, see `Type::is_cheap`.
+/// Ones whose result can grow without bound like `*`, `^` and `concat` aren't here,
+/// because it's wasted when the argument isn't used
+const CHEAP_BUILTINS: [&str; 14] = [
+    "+", "-", "%", "=", "!=", ">", ">=", "<", "<=", "&", "|", "!", "car", "cdr",
+];
+
+/// Builtins that have no side effect and always evaluate all of their arguments, see `Thunk::force`
+const STRICT_BUILTINS: [&str; 18] = [
+    "+", "-", "*", "/", "%", "^", "=", "!=", ">", ">=", "<", "<=", "&", "|", "!", "concat", "car",
+    "cdr",
+];
+
+fn stdlib() -> Scope {
+    Scope::from(HashMap::from([
+        builtin(
+            "+",
+            Arity::AtLeast(1),
+            "Sum of the numbers",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                let params: Vec<Number> = params
+                    .iter()
+                    .map(|i| i.expect_number(scope))
+                    .collect::<Result<_, _>>()?;
+                let mut result = params[0].clone();
+                for i in params[1..].iter() {
+                    result = &result + i;
+                }
+                Ok(Type::Number(result))
+            },
+        ),
+        builtin(
+            "-",
+            Arity::AtLeast(1),
+            "Difference of the numbers, or negation of the only one",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                let params: Vec<Number> = params
+                    .iter()
+                    .map(|i| i.expect_number(scope))
+                    .collect::<Result<_, _>>()?;
+                if params.len() >= 2 {
+                    let mut result = params[0].clone();
+                    for i in params[1..].iter() {
+                        result = &result - i;
+                    }
+                    Ok(Type::Number(result))
+                } else {
+                    Ok(Type::Number(-&params[0]))
+                }
+            },
+        ),
+        builtin(
+            "*",
+            Arity::AtLeast(1),
+            "Product of the numbers",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                let params: Vec<Number> = params
+                    .iter()
+                    .map(|i| i.expect_number(scope))
+                    .collect::<Result<_, _>>()?;
+                let mut result = params[0].clone();
+                for i in params[1..].iter() {
+                    scope
+                        .runtime()
+                        .check_len("number", result.bits().saturating_add(i.bits()))?;
+                    result = &result * i;
+                }
+                Ok(Type::Number(result))
+            },
+        ),
+        builtin(
+            "/",
+            Arity::AtLeast(1),
+            "Quotient of the numbers, that's exact unless floats are mixed",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                let params: Vec<Number> = params
+                    .iter()
+                    .map(|i| i.expect_number(scope))
+                    .collect::<Result<_, _>>()?;
+                let mut result = params[0].clone();
+                for i in params[1..].iter() {
+                    result = result.divide(i)?;
+                }
+                Ok(Type::Number(result))
+            },
+        ),
+        builtin(
+            "%",
+            Arity::AtLeast(1),
+            "Remainder of the division",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                let params: Vec<Number> = params
+                    .iter()
+                    .map(|i| i.expect_number(scope))
+                    .collect::<Result<_, _>>()?;
+                let mut result = params[0].clone();
+                for i in params[1..].iter() {
+                    result = result.remainder(i)?;
+                }
+                Ok(Type::Number(result))
+            },
+        ),
+        builtin(
+            "^",
+            Arity::AtLeast(1),
+            "Power of the number",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                let params: Vec<Number> = params
+                    .iter()
+                    .map(|i| i.expect_number(scope))
+                    .collect::<Result<_, _>>()?;
+                let mut result = params[0].clone();
+                for i in params[1..].iter() {
+                    // Computing the large power takes long time, so the size is checked before it
+                    scope
+                        .runtime()
+                        .check_len("number", result.power_bits(i))?;
+                    result = result.power(i)?;
+                }
+                Ok(Type::Number(result))
+            },
+        ),
+        builtin(
+            "exact->inexact",
+            Arity::Exact(1),
+            "Float that's nearest to the number",
+            |params, scope| {
+                let number = params[0].eval(scope)?.expect_number(scope)?;
+                Ok(Type::Number(Number::Float(number.to_f64())))
+            },
+        ),
+        builtin(
+            "floor",
+            Arity::Exact(1),
+            "Largest integer that's not greater than the number",
+            |params, scope| {
+                Ok(Type::Number(
+                    match params[0].eval(scope)?.expect_number(scope)? {
+                        Number::Rational(r) => Number::Integer(r.floor().to_integer()),
+                        Number::Float(f) => Number::Float(f.floor()),
+                        integer => integer,
+                    },
+                ))
+            },
+        ),
+        builtin(
+            "round",
+            Arity::Exact(1),
+            "Nearest integer of the number",
+            |params, scope| {
+                // Halfway cases are rounded away from zero
+                Ok(Type::Number(
+                    match params[0].eval(scope)?.expect_number(scope)? {
+                        Number::Rational(r) => Number::Integer(r.round().to_integer()),
+                        Number::Float(f) => Number::Float(f.round()),
+                        integer => integer,
+                    },
+                ))
+            },
+        ),
+        builtin(
+            "numerator",
+            Arity::Exact(1),
+            "Numerator of the exact number",
+            |params, scope| {
+                let number = params[0].eval(scope)?.expect_number(scope)?;
+                Ok(Type::Number(number.fraction(|r| r.numer().clone())?))
+            },
+        ),
+        builtin(
+            "denominator",
+            Arity::Exact(1),
+            "Denominator of the exact number",
+            |params, scope| {
+                let number = params[0].eval(scope)?.expect_number(scope)?;
+                Ok(Type::Number(number.fraction(|r| r.denom().clone())?))
+            },
+        ),
+        builtin(
+            "concat",
+            Arity::AtLeast(0),
+            "String that joins the values",
+            |params, scope| {
+                Ok(Type::String({
+                    let mut result = "".to_string();
+                    for i in params {
+                        result += &i.eval(scope)?.get_string();
+                    }
+                    result
+                }))
+            },
+        ),
+        builtin(
+            "print",
+            Arity::AtLeast(0),
+            "Print the values without new line",
+            |params, scope| {
+                scope.runtime().require(Capability::Print, "print")?;
+                for i in params {
+                    print!("{}", i.eval(scope)?.realized()?.get_string())
+                }
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "format",
+            Arity::Exact(2),
+            "Replace `{}` in the string with the value",
+            |params, scope| {
+                Ok(Type::String(
+                    params[0]
+                        .eval(scope)?
+                        .get_string()
+                        .replace("{}", &params[1].eval(scope)?.get_string()),
+                ))
+            },
+        ),
+        builtin(
+            "debug",
+            Arity::AtLeast(0),
+            "Print the code and its value",
+            |params, scope| {
+                scope.runtime().require(Capability::Print, "debug")?;
+                for i in params {
+                    println!("Debug: {i:?} = {:?}", i.eval(scope)?.realized()?)
+                }
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "input",
+            Arity::Range(0, 1),
+            "Read a line from standard input, after printing the prompt",
+            |params, scope| {
+                scope.runtime().require(Capability::Input, "input")?;
+                Ok(Type::String({
+                    let mut input = String::new();
+                    if let Some(prompt) = params.first() {
+                        print!("{}", prompt.eval(scope)?.get_string());
+                    }
+                    io::stdout().flush().unwrap_or_default();
+                    match io::stdin().read_line(&mut input) {
+                        Ok(_) => input.trim().to_string(),
+                        Err(err) => {
+                            return Err(LazoError::Io(format!("reading line was fault: {err}")))
+                        }
+                    }
+                }))
+            },
+        ),
+        builtin(
+            "=",
+            Arity::AtLeast(2),
+            "Whether all of the values are equal",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool(
+                    Type::all_equal(&params)?,
+                ))
+            },
+        ),
+        builtin(
+            "!=",
+            Arity::AtLeast(2),
+            "Whether any of the values is different",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                // It's true unless all of them are equal
+                Ok(Type::Bool(
+                    !Type::all_equal(&params)?,
+                ))
+            },
+        ),
+        builtin(
+            "eq?",
+            Arity::AtLeast(2),
+            "Whether all of the values are identical",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool(
+                    params.windows(2).all(|window| window[0].is(&window[1])),
+                ))
+            },
+        ),
+        builtin(
+            "equal?",
+            Arity::AtLeast(2),
+            "Whether all of the values are deeply equal",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool(
+                    Type::all_equal(&params)?,
+                ))
+            },
+        ),
+        builtin(
+            ">",
+            Arity::AtLeast(2),
+            "Whether the numbers are decreasing",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool({
+                    let params: Vec<Number> = params
+                        .iter()
+                        .map(|i| i.expect_number(scope))
+                        .collect::<Result<_, _>>()?;
+                    params.windows(2).all(|window| window[0] > window[1])
+                }))
+            },
+        ),
+        builtin(
+            ">=",
+            Arity::AtLeast(2),
+            "Whether the numbers are not increasing",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool({
+                    let params: Vec<Number> = params
+                        .iter()
+                        .map(|i| i.expect_number(scope))
+                        .collect::<Result<_, _>>()?;
+                    params.windows(2).all(|window| window[0] >= window[1])
+                }))
+            },
+        ),
+        builtin(
+            "<",
+            Arity::AtLeast(2),
+            "Whether the numbers are increasing",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool({
+                    let params: Vec<Number> = params
+                        .iter()
+                        .map(|i| i.expect_number(scope))
+                        .collect::<Result<_, _>>()?;
+                    params.windows(2).all(|window| window[0] < window[1])
+                }))
+            },
+        ),
+        builtin(
+            "<=",
+            Arity::AtLeast(2),
+            "Whether the numbers are not decreasing",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool({
+                    let params: Vec<Number> = params
+                        .iter()
+                        .map(|i| i.expect_number(scope))
+                        .collect::<Result<_, _>>()?;
+                    params.windows(2).all(|window| window[0] <= window[1])
+                }))
+            },
+        ),
+        builtin(
+            "&",
+            Arity::AtLeast(2),
+            "Whether all of the values are true",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool({
+                    let params: Vec<bool> = params
+                        .iter()
+                        .map(|i| i.expect_bool(scope))
+                        .collect::<Result<_, _>>()?;
+                    params.iter().all(|x| *x)
+                }))
+            },
+        ),
+        builtin(
+            "|",
+            Arity::AtLeast(2),
+            "Whether any of the values is true",
+            |params, scope| {
+                let params = {
+                    let mut new = vec![];
+                    for i in params {
+                        new.push(i.eval(scope)?)
+                    }
+                    new
+                };
+
+                Ok(Type::Bool({
+                    let params: Vec<bool> = params
+                        .iter()
+                        .map(|i| i.expect_bool(scope))
+                        .collect::<Result<_, _>>()?;
+                    params.iter().any(|x| *x)
+                }))
+            },
+        ),
+        builtin(
+            "!",
+            Arity::Exact(1),
+            "Negation of the bool",
+            |params, scope| Ok(Type::Bool(!params[0].eval(scope)?.expect_bool(scope)?)),
+        ),
+        builtin(
+            "cast",
+            Arity::Exact(2),
+            "Convert the value to the type",
+            |params, scope| {
+                let value = params[0].eval(scope)?;
+                let name = match &params[1] {
+                    Type::Symbol(name) => name.clone(),
+                    other => other.eval(scope)?.get_string(),
+                };
+                match name.as_str() {
+                    "number" => Ok(Type::Number(value.get_number())),
+                    "string" => Ok(Type::String(value.get_string())),
+                    "bool" => Ok(Type::Bool(value.get_bool())),
+                    "list" => Ok(Type::List(value.get_list())),
+                    "map" => Ok(Type::Map(value.get_map())),
+                    other => Err(LazoError::Runtime(format!("unknown type name `{other}`"))),
+                }
+            },
+        ),
+        builtin(
+            "type",
+            Arity::Exact(1),
+            "Name of the value's type",
+            |params, scope| Ok(Type::String(params[0].eval(scope)?.get_type())),
+        ),
+        builtin(
+            "eval",
+            Arity::AtLeast(0),
+            "Evaluate the values as code",
+            |params, scope| {
+                // Passed values are evaluated again as code
+                if let Some((last, params)) = params.split_last() {
+                    for expr in params {
+                        expr.eval(scope)?.eval(scope)?;
+                    }
+                    Ok(last.eval(scope)?.tail(scope))
+                } else {
+                    Ok(Type::Null)
+                }
+            },
+        ),
+        builtin(
+            "begin",
+            Arity::AtLeast(0),
+            "Evaluate the code in order and get the last value",
+            |params, scope| Type::eval_block(&params, scope),
+        ),
+        builtin(
+            "quote",
+            Arity::Exact(1),
+            "Code as it is without evaluation",
+            |params, _| Ok(params[0].clone()),
+        ),
+        builtin(
+            "quasiquote",
+            Arity::Exact(1),
+            "Code from the template, that's unquoted parts are evaluated",
+            |params, scope| params[0].quasiquote(0, scope),
+        ),
+        builtin(
+            "unquote",
+            Arity::Exact(1),
+            "Part of `quasiquote` that's evaluated",
+            |_, _| {
+                Err(LazoError::Syntax(
+                    "unquote should be used in quasiquote".to_string(),
+                ))
+            },
+        ),
+        builtin(
+            "unquote-splicing",
+            Arity::Exact(1),
+            "Part of `quasiquote` that's evaluated and spliced in the list",
+            |_, _| {
+                Err(LazoError::Syntax(
+                    "unquote-splicing should be used in list of quasiquote".to_string(),
+                ))
+            },
+        ),
+        builtin(
+            "define",
+            Arity::AtLeast(2),
+            "Define the variable or the function",
+            |params, scope| {
+                let value: Type;
+                if let Type::List(args) | Type::Expr(args, _) = params[0].clone() {
+                    value = Type::Function(Function::UserDefined(Rc::new(Closure::new(
+                        Some(args[0].get_string()),
+                        &args[1..],
+                        &params[1..],
+                        scope,
+                    )?)));
+                    scope.insert(args[0].get_string(), value.clone());
+                } else {
+                    value = params[1].suspend(scope);
+                    scope.insert(params[0].get_string(), value.clone());
+                }
+                Ok(value)
+            },
+        ),
+        builtin(
+            "lambda",
+            Arity::AtLeast(2),
+            "Function with the arguments and the body",
+            |params, scope| {
+                Ok(Type::Function(Function::UserDefined(Rc::new(
+                    Closure::new(None, &params[0].get_list(), &params[1..], scope)?,
+                ))))
+            },
+        ),
+        builtin(
+            "let",
+            Arity::AtLeast(1),
+            "Evaluate the body with the local variables",
+            |params, scope| {
+                let mut let_scope = Scope::new(scope);
+                for (name, value) in bindings(&params[0])? {
+                    let_scope.insert(name, value.suspend(scope));
+                }
+                Type::eval_block(&params[1..], &mut let_scope)
+            },
+        ),
+        builtin(
+            "let*",
+            Arity::AtLeast(1),
+            "`let` that each binding can refer to the previous ones",
+            |params, scope| {
+                // Each binding can refer to the previous ones
+                let mut let_scope = scope.clone();
+                for (name, value) in bindings(&params[0])? {
+                    let value = value.suspend(&let_scope);
+                    let_scope = Scope::new(&let_scope);
+                    let_scope.insert(name, value);
+                }
+                Type::eval_block(&params[1..], &mut let_scope)
+            },
+        ),
+        builtin(
+            "letrec",
+            Arity::AtLeast(1),
+            "`let` that each binding can refer to all of them",
+            |params, scope| {
+                // Each binding can refer to all of them including itself
+                let mut let_scope = Scope::new(scope);
+                for (name, value) in bindings(&params[0])? {
+                    let_scope.insert(name, value.suspend(&let_scope));
+                }
+                Type::eval_block(&params[1..], &mut let_scope)
+            },
+        ),
+        builtin(
+            "set!",
+            Arity::Exact(2),
+            "Overwrite the variable that's already defined",
+            |params, scope| {
+                let value = match params[1].eval(scope)? {
+                    Type::Thunk(thunk) => Type::Thunk(Thunk::forced(Type::Thunk(thunk))),
+                    value => value,
+                };
+                scope.set(&params[0].get_string(), value.clone())?;
+                Ok(value)
+            },
+        ),
+        builtin(
+            "syntax-rules",
+            Arity::AtLeast(1),
+            "Macro that rewrites the code by the patterns",
+            |params, scope| {
+                let mut rules = vec![];
+                for rule in &params[1..] {
+                    match rule.get_list().as_slice() {
+                    [pattern, template] => rules.push((pattern.clone(), template.clone())),
+                    _ => {
+                        return Err(LazoError::Syntax(format!(
+                            "syntax rule should be pair of the pattern and the template, but provided `{rule:?}` is not"
+                        )))
+                    }
+                }
+                }
+                Ok(Type::Function(Function::Macro(Rc::new(Macro {
+                    literals: params[0]
+                        .get_list()
+                        .iter()
+                        .map(|i| i.get_string())
+                        .collect(),
+                    rules,
+                    env: scope.clone(),
+                }))))
+            },
+        ),
+        builtin(
+            "define-record",
+            Arity::Exact(2),
+            "Define the record type with its constructor, predicate, accessors and updaters",
+            |params, scope| {
+                let (Type::Symbol(name), Type::Expr(fields, _) | Type::List(fields)) =
+                    (&params[0], &params[1])
+                else {
+                    return Err(LazoError::Syntax(format!(
+                        "record should be defined like `(define-record point (x y))`, but provided `{:?}` is not",
+                        params[0]
+                    )));
+                };
+                if TYPE_NAMES.contains(&name.as_str()) {
+                    return Err(LazoError::Syntax(format!(
+                        "`{name}` is the builtin type, that can't be the name of the record"
+                    )));
+                }
+                let mut names: Vec<String> = vec![];
+                for field in fields {
+                    match field {
+                        Type::Symbol(field) if !names.contains(field) => names.push(field.clone()),
+                        _ => {
+                            return Err(LazoError::Syntax(format!(
+                                "field of the record should be unique name, but provided `{field:?}` is not"
+                            )))
+                        }
+                    }
+                }
+                scope.runtime().records.borrow_mut().insert(name.clone());
+                let kind = Rc::new(RecordType {
+                    name: name.clone(),
+                    fields: names,
+                });
+                let functions = kind.functions();
+                let constructor = functions[0].1.clone();
+                for (name, func) in functions {
+                    scope.insert(name, func);
+                }
+                Ok(constructor)
+            },
+        ),
+        builtin(
+            "define-syntax",
+            Arity::Exact(2),
+            "Define the macro",
+            |params, scope| {
+                let value = params[1].eval(scope)?;
+                if let Type::Function(Function::Macro(_)) = value {
+                    scope.insert(params[0].get_string(), value.clone());
+                    Ok(value)
+                } else {
+                    Err(LazoError::Syntax(format!(
+                        "macro should be defined by `syntax-rules`, but provided `{:?}` is not",
+                        params[1]
+                    )))
+                }
+            },
+        ),
+        builtin(
+            "if",
+            Arity::Range(2, 3),
+            "Evaluate the branch by the condition",
+            |params, scope| {
+                if params.len() == 3 {
+                    if params[0].eval(scope)?.get_bool() {
+                        Ok(params[1].tail(scope))
+                    } else {
+                        Ok(params[2].tail(scope))
+                    }
+                } else if params[0].eval(scope)?.get_bool() {
+                    Ok(params[1].tail(scope))
+                } else {
+                    Ok(Type::Null)
+                }
+            },
+        ),
+        builtin(
+            "cond",
+            Arity::AtLeast(0),
+            "Evaluate the first clause whose condition is true",
+            |params, scope| {
+                for i in params {
+                    let clause = i.get_list();
+                    if clause.len() < 2 {
+                        return Err(LazoError::Syntax(format!(
+                            "clause of cond should be like `(condition body)`, but provided `{i:?}` is not"
+                        )));
+                    }
+                    let otherwise = matches!(&clause[0], Type::Symbol(name) if name == "else");
+                    if otherwise || clause[0].eval(scope)?.get_bool() {
+                        return Ok(clause[1].tail(scope));
+                    }
+                }
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "match",
+            Arity::AtLeast(1),
+            "Evaluate the first clause whose pattern matches the value, with the variables of it",
+            |params, scope| {
+                let mut clauses = vec![];
+                for i in &params[1..] {
+                    clauses.push(clause(i)?);
+                }
+                let value = params[0].eval(scope)?;
+                for (pattern, guard, body) in clauses {
+                    let mut binds = vec![];
+                    if !destructure(pattern, value.clone(), &mut binds)? {
+                        continue;
+                    }
+                    let mut scope = Scope::new(scope);
+                    for (name, value) in binds {
+                        scope.insert(name, value);
+                    }
+                    match guard {
+                        Some(guard) if !guard.eval(&mut scope)?.get_bool() => continue,
+                        _ => return Type::eval_block(body, &mut scope),
+                    }
+                }
+                Err(LazoError::Match(value))
+            },
+        ),
+        builtin(
+            "car",
+            Arity::Exact(1),
+            "First element of the list or the stream",
+            |params, scope| {
+                let list = params[0].eval(scope)?;
+                if let Some(stream) = list.stream() {
+                    return Ok(stream.next()?.map(|(first, _)| first).unwrap_or(Type::Null));
+                }
+                Ok(list
+                    .expect_list(scope)?
+                    .first()
+                    .unwrap_or(&Type::Null)
+                    .clone())
+            },
+        ),
+        builtin(
+            "cdr",
+            Arity::Exact(1),
+            "List or stream without the first element",
+            |params, scope| {
+                let list = params[0].eval(scope)?;
+                let empty = || LazoError::Runtime("passed list is empty".to_string());
+                if let Some(stream) = list.stream() {
+                    return match stream.next()? {
+                        Some((_, rest)) => Ok(Type::Stream(rest)),
+                        None => Err(empty()),
+                    };
+                }
+                match list.expect_list(scope)?.split_first() {
+                    Some((_, rest)) => Ok(Type::List(rest.to_vec())),
+                    None => Err(empty()),
+                }
+            },
+        ),
+        builtin(
+            "range",
+            Arity::Range(0, 3),
+            "Stream of the numbers from the start to the end by the step, it's endless without the end",
+            |params, scope| {
+                let zero = Number::Integer(BigInt::zero());
+                let one = Number::Integer(BigInt::from(1));
+                // Bounds are evaluated once, `null` end makes it endless
+                let mut bounds = vec![];
+                for i in &params {
+                    bounds.push(match i.eval(scope)? {
+                        Type::Null => None,
+                        bound => Some(bound.expect_number(scope)?),
+                    });
+                }
+                let (start, end, step) = match bounds.as_slice() {
+                    [] => (None, None, None),
+                    [end] => (None, end.clone(), None),
+                    [start, end] => (start.clone(), end.clone(), None),
+                    [start, end, step, ..] => (start.clone(), end.clone(), step.clone()),
+                };
+                // Zero step never reaches the end
+                if step.as_ref().is_some_and(Number::is_zero) {
+                    return Err(LazoError::Runtime(
+                        "step of the range should not be zero".to_string(),
+                    ));
+                }
+                Ok(Type::Stream(Stream::new(
+                    Generator::Range(start.unwrap_or(zero), end, step.unwrap_or(one)),
+                    scope,
+                )))
+            },
+        ),
+        builtin(
+            "iterate",
+            Arity::Exact(2),
+            "Endless stream of the value and the function applied to the previous one",
+            |params, scope| {
+                let value = params[0].eval(scope)?;
+                let func = params[1].eval(scope)?;
+                let rest = Stream::new(Generator::Iterate(value.clone(), func), scope);
+                Ok(Type::Stream(Stream::with(Node::Cons(value, rest), scope)))
+            },
+        ),
+        builtin(
+            "cycle",
+            Arity::Exact(1),
+            "Endless stream that repeats the elements of the list",
+            |params, scope| {
+                let list = params[0].eval(scope)?.expect_list(scope)?;
+                Ok(Type::Stream(Stream::new(
+                    Generator::Cycle(Rc::new(list), 0),
+                    scope,
+                )))
+            },
+        ),
+        builtin(
+            "take",
+            Arity::Exact(2),
+            "First elements of the list or the stream up to the count",
+            |params, scope| {
+                let seq = params[0].eval(scope)?;
+                let count = params[1].eval(scope)?.expect_number(scope)?.to_f64() as usize;
+                let stream = Stream::new(Generator::Take(seq.expect_stream(scope)?, count), scope);
+                stream.lazy_if(seq.stream().is_some())
+            },
+        ),
+        builtin(
+            "drop",
+            Arity::Exact(2),
+            "List or stream without the first elements of the count",
+            |params, scope| {
+                let seq = params[0].eval(scope)?;
+                let count = params[1].eval(scope)?.expect_number(scope)?.to_f64() as usize;
+                let stream = Stream::new(Generator::Drop(seq.expect_stream(scope)?, count), scope);
+                stream.lazy_if(seq.stream().is_some())
+            },
+        ),
+        builtin(
+            "take-while",
+            Arity::Exact(2),
+            "First elements while the function returns true for them",
+            |params, scope| {
+                let seq = params[0].eval(scope)?;
+                let func = params[1].eval(scope)?;
+                let stream =
+                    Stream::new(Generator::TakeWhile(seq.expect_stream(scope)?, func), scope);
+                stream.lazy_if(seq.stream().is_some())
+            },
+        ),
+        builtin(
+            "zip",
+            Arity::AtLeast(1),
+            "Lists of the elements at the same position, up to the shortest one",
+            |params, scope| {
+                let (mut streams, mut lazy) = (vec![], false);
+                for i in &params {
+                    let seq = i.eval(scope)?;
+                    lazy |= seq.stream().is_some();
+                    streams.push(seq.expect_stream(scope)?);
+                }
+                Stream::new(Generator::Zip(streams), scope).lazy_if(lazy)
+            },
+        ),
+        builtin(
+            "map",
+            Arity::Exact(2),
+            "List of the function's results for each element, it's lazy for the stream",
+            |params, scope| {
+                let mut result = vec![];
+                let func = params[1].eval(scope)?.clone();
+                let list = params[0].eval(scope)?;
+                if let Some(stream) = list.stream() {
+                    return Ok(Type::Stream(Stream::new(Generator::Map(stream, func), scope)));
+                }
+                for i in list.expect_list(scope)? {
+                    result.push(Type::Expr(vec![func.clone(), i.quote()], None).eval(scope)?);
+                }
+                Ok(Type::List(result))
+            },
+        ),
+        builtin(
+            "for",
+            Arity::Exact(2),
+            "Call the function with each element",
+            |params, scope| {
+                let func = params[1].eval(scope)?.clone();
+                let list = params[0].eval(scope)?;
+                if let Some(mut stream) = list.stream() {
+                    // Elements that're passed aren't kept, so the endless stream runs in constant memory
+                    drop(list);
+                    while let Some((i, rest)) = stream.next()? {
+                        Type::Expr(vec![func.clone(), i.quote()], None).eval(scope)?;
+                        stream = rest;
+                    }
+                    return Ok(Type::Null);
+                }
+                for i in list.expect_list(scope)? {
+                    Type::Expr(vec![func.clone(), i.quote()], None).eval(scope)?;
+                }
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "filter",
+            Arity::Exact(2),
+            "Elements that the function returns true for, it's lazy for the stream",
+            |params, scope| {
+                let mut result = vec![];
+                let func = params[1].eval(scope)?.clone();
+                let list = params[0].eval(scope)?;
+                if let Some(stream) = list.stream() {
+                    return Ok(Type::Stream(Stream::new(
+                        Generator::Filter(stream, func),
+                        scope,
+                    )));
+                }
+                for i in list.expect_list(scope)? {
+                    if Type::Expr(vec![func.to_owned(), i.quote()], None)
+                        .eval(scope)?
+                        .get_bool()
+                    {
+                        result.push(i)
+                    }
+                }
+                Ok(Type::List(result))
+            },
+        ),
+        builtin(
+            "reduce",
+            Arity::Exact(2),
+            "Fold the list by the function from the left",
+            |params, scope| {
+                let func = params[1].clone();
+                let list = params[0].eval(scope)?.expect_list(scope)?;
+                let mut result = if let Some(first) = list.first() {
+                    first.clone()
+                } else {
+                    return Err(LazoError::Runtime("passed list is empty".to_string()));
+                };
+
+                let mut scope = scope.clone();
+                for i in list[1..].iter() {
+                    result = Type::Expr(vec![func.clone(), result.quote(), i.quote()], None)
+                        .eval(&mut scope)?
+                }
+                Ok(result.to_owned())
+            },
+        ),
+        builtin(
+            "reverse",
+            Arity::Exact(1),
+            "List in reverse order",
+            |params, scope| {
+                let mut list = params[0].eval(scope)?.expect_list(scope)?;
+                list.reverse();
+                Ok(Type::List(list))
+            },
+        ),
+        builtin(
+            "len",
+            Arity::Exact(1),
+            "Length of the list or the string",
+            |params, scope| {
+                let list = params[0].eval(scope)?;
+                if let Type::String(text) = &list {
+                    return Ok(Type::Number(Number::Integer(text.chars().count().into())));
+                }
+                if let Some(mut stream) = list.stream() {
+                    // Counted elements aren't kept, like `for`
+                    drop(list);
+                    let mut len = 0usize;
+                    while let Some((_, rest)) = stream.next()? {
+                        len += 1;
+                        stream = rest;
+                    }
+                    return Ok(Type::Number(Number::Integer(len.into())));
+                }
+                Ok(Type::Number(Number::Integer(
+                    list.expect_list(scope)?.len().into(),
+                )))
+            },
+        ),
+        builtin(
+            "repeat",
+            Arity::Exact(2),
+            "String that's repeated the times",
+            |params, scope| {
+                let text = params[0].eval(scope)?.get_string();
+                let times = params[1].eval(scope)?.expect_number(scope)?.to_f64() as usize;
+                // Checking before making it, that's too large to be made
+                let len = text.chars().count().saturating_mul(times);
+                scope.runtime().check_len("string", len)?;
+                Ok(Type::String(text.repeat(times)))
+            },
+        ),
+        builtin(
+            "join",
+            Arity::Exact(2),
+            "String that joins the elements with the separator",
+            |params, scope| {
+                Ok(Type::String(
+                    params[0]
+                        .eval(scope)?
+                        .expect_list(scope)?
+                        .iter()
+                        .map(|i| i.get_string())
+                        .collect::<Vec<String>>()
+                        .join(&params[1].eval(scope)?.get_string()),
+                ))
+            },
+        ),
+        builtin(
+            "split",
+            Arity::Exact(2),
+            "Strings that're split by the separator",
+            |params, scope| {
+                Ok(Type::List(
+                    params[0]
+                        .eval(scope)?
+                        .get_string()
+                        .split(&params[1].eval(scope)?.get_string())
+                        .map(|i| Type::String(i.to_string()))
+                        .collect::<Vec<Type>>(),
+                ))
+            },
+        ),
+        builtin(
+            "get",
+            Arity::Range(2, 3),
+            "Value of the key in the map, or the default",
+            |params, scope| {
+                let map = params[0].eval(scope)?.get_map();
+                match map.get(&params[1].eval(scope)?.key()) {
+                    Some((_, value)) => Ok(value.clone()),
+                    None => match params.get(2) {
+                        Some(default) => default.eval(scope),
+                        None => Ok(Type::Null),
+                    },
+                }
+            },
+        ),
+        builtin(
+            "assoc",
+            Arity::AtLeast(3),
+            "Map that the keys are set to the values",
+            |params, scope| {
+                if params.len() % 2 == 1 {
+                    let mut map = params[0].eval(scope)?.get_map();
+                    for pair in params[1..].chunks(2) {
+                        let key = pair[0].eval(scope)?;
+                        map.insert(key.key(), (key, pair[1].eval(scope)?));
+                    }
+                    Ok(Type::Map(map))
+                } else {
+                    Err(LazoError::Syntax(format!(
+                    "assoc should be given pairs of the key and the value, but `{:?}` has no value",
+                    params[params.len() - 1]
+                )))
+                }
+            },
+        ),
+        builtin(
+            "dissoc",
+            Arity::AtLeast(1),
+            "Map without the keys",
+            |params, scope| {
+                let mut map = params[0].eval(scope)?.get_map();
+                for key in &params[1..] {
+                    map.remove(&key.eval(scope)?.key());
+                }
+                Ok(Type::Map(map))
+            },
+        ),
+        builtin(
+            "keys",
+            Arity::Exact(1),
+            "Keys of the map",
+            |params, scope| {
+                let map = params[0].eval(scope)?.get_map();
+                Ok(Type::List(map.into_values().map(|(k, _)| k).collect()))
+            },
+        ),
+        builtin(
+            "values",
+            Arity::Exact(1),
+            "Values of the map",
+            |params, scope| {
+                let map = params[0].eval(scope)?.get_map();
+                Ok(Type::List(map.into_values().map(|(_, v)| v).collect()))
+            },
+        ),
+        builtin(
+            "has?",
+            Arity::Exact(2),
+            "Whether the map has the key",
+            |params, scope| {
+                let map = params[0].eval(scope)?.get_map();
+                Ok(Type::Bool(map.contains_key(&params[1].eval(scope)?.key())))
+            },
+        ),
+        builtin(
+            "merge",
+            Arity::AtLeast(0),
+            "Map that merges the maps, latter ones take precedence",
+            |params, scope| {
+                // Latter map overwrites the values of the same keys
+                let mut result = BTreeMap::new();
+                for map in params {
+                    result.extend(map.eval(scope)?.get_map());
+                }
+                Ok(Type::Map(result))
+            },
+        ),
+        builtin(
+            "error",
+            Arity::Range(0, 2),
+            "Raise the error with the message, or the kind and the payload",
+            |params, scope| {
+                // Error with the kind like `(error 'not-found key)`, or just the message
+                match params.as_slice() {
+                    [] => Err(LazoError::Runtime("Something went wrong".to_string())),
+                    [message] => Err(LazoError::Runtime(message.eval(scope)?.get_string())),
+                    [kind, payload, ..] => Err(LazoError::Raised(
+                        kind.eval(scope)?.get_string(),
+                        payload.eval(scope)?,
+                    )),
+                }
+            },
+        ),
+        builtin(
+            "raise",
+            Arity::Exact(1),
+            "Raise the caught error again",
+            |params, scope| {
+                // Caught error is thrown again as it is
+                match params[0].eval(scope)? {
+                    Type::Error(err) => Err((*err).clone()),
+                    other => Err(LazoError::Runtime(other.get_string())),
+                }
+            },
+        ),
+        builtin(
+            "try",
+            Arity::AtLeast(1),
+            "Evaluate the code with `catch` and `finally` handlers",
+            |params, scope| {
+                let (body, handlers) = (&params[0], &params[1..]);
+                let mut catch = None;
+                let mut finally = vec![];
+                for handler in handlers {
+                    let form = match handler {
+                        Type::Expr(form, _) => form.as_slice(),
+                        _ => &[],
+                    };
+                    match form {
+                    [Type::Symbol(keyword), Type::Symbol(name), block @ ..]
+                        if keyword == "catch" =>
+                    {
+                        catch = Some((Some(name.to_owned()), block.to_vec()))
+                    }
+                    [Type::Symbol(keyword), block @ ..] if keyword == "finally" => {
+                        finally = block.to_vec()
+                    }
+                    // Value that's used instead when it fails, like `(try x 0)`
+                    _ if handlers.len() == 1 => catch = Some((None, vec![handler.clone()])),
+                    _ => {
+                        return Err(LazoError::Syntax(format!(
+                            "handler of try should be `(catch e ...)` or `(finally ...)`, but provided `{handler:?}` is not"
+                        )))
+                    }
+                }
+                }
+
+                let result = match (body.eval(scope), catch) {
+                    // Program can't escape from the sandbox by catching its violation
+                    (Err(err), Some((name, block))) if !err.is_limit() => {
+                        let mut catch_scope = Scope::new(scope);
+                        if let Some(name) = name {
+                            catch_scope.insert(name, Type::Error(Rc::new(err)));
+                        }
+                        Type::eval_block(&block, &mut catch_scope).and_then(Type::resolve)
+                    }
+                    (result, _) => result,
+                };
+                // Cleanup runs whether it's succeeded or not
+                for line in &finally {
+                    line.eval(scope)?;
+                }
+                result
+            },
+        ),
+        builtin(
+            "error-kind",
+            Arity::Exact(1),
+            "Kind of the error",
+            |params, scope| match params[0].eval(scope)? {
+                Type::Error(err) => Ok(Type::Symbol(err.kind())),
+                other => Err(other.mismatch("error")),
+            },
+        ),
+        builtin(
+            "error-message",
+            Arity::Exact(1),
+            "Message of the error",
+            |params, scope| match params[0].eval(scope)? {
+                Type::Error(err) => Ok(Type::String(err.message())),
+                other => Err(other.mismatch("error")),
+            },
+        ),
+        builtin(
+            "error-payload",
+            Arity::Exact(1),
+            "Value that the error carries",
+            |params, scope| match params[0].eval(scope)? {
+                Type::Error(err) => Ok(err.payload()),
+                other => Err(other.mismatch("error")),
+            },
+        ),
+        builtin(
+            "exit",
+            Arity::Range(0, 1),
+            "Exit the process with the status code",
+            |params, scope| {
+                scope.runtime().require(Capability::Exit, "exit")?;
+                std::process::exit(
+                    params
+                        .first()
+                        .unwrap_or(&Type::Number(Number::Integer(BigInt::zero())))
+                        .eval(scope)?
+                        .get_number()
+                        .to_f64() as i32,
+                )
+            },
+        ),
+        builtin(
+            "delay",
+            Arity::Exact(1),
+            "Suspend the code to evaluate it later",
+            |params, scope| Ok(Type::Thunk(Thunk::new(params[0].clone(), scope.clone()))),
+        ),
+        builtin(
+            "force",
+            Arity::Exact(1),
+            "Value of the suspended code",
+            |params, scope| {
+                let mut value = params[0].eval(scope)?;
+                while let Type::Thunk(thunk) = value {
+                    value = thunk.force()?;
+                }
+                Ok(value)
+            },
+        ),
+        builtin(
+            "lazy?",
+            Arity::Exact(1),
+            "Whether the value is still suspended",
+            |params, scope| {
+                // Looking the binding up without forcing it
+                let value = match &params[0] {
+                    Type::Symbol(name) => scope.get(name),
+                    _ => None,
+                };
+                Ok(Type::Bool(match value {
+                    Some(value) => matches!(value, Type::Thunk(thunk) if thunk.is_lazy()),
+                    None => {
+                        matches!(params[0].eval(scope)?, Type::Thunk(thunk) if thunk.is_lazy())
+                    }
+                }))
+            },
+        ),
+        builtin(
+            "import",
+            Arity::AtLeast(1),
+            "Load the module and bind its exports",
+            |params, scope| {
+                scope.runtime().require(Capability::Io, "import")?;
+                let (target, options) = (&params[0], &params[1..]);
+                let only = match options {
+                [] => None,
+                [Type::Symbol(option), names] if option == ":only" => {
+                    Some(names.get_list().iter().map(|i| i.get_string()).collect())
+                }
+                _ => {
+                    return Err(LazoError::Syntax(format!(
+                        "import should be like `(import math :only (sqrt))`, but provided `{:?}` is not",
+                        Type::List(options.to_vec())
+                    )))
+                }
+            };
+                let exports = load(resolve(target, scope)?, scope)?;
+                let names: Vec<String> = only.unwrap_or(exports.keys().cloned().collect());
+                for name in names {
+                    let Some(value) = exports.get(&name) else {
+                        return Err(LazoError::Runtime(format!(
+                            "module `{}` doesn't export `{name}`",
+                            target.get_string()
+                        )));
+                    };
+                    scope.insert(name, value.clone());
+                }
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "export",
+            Arity::AtLeast(0),
+            "Names that the module exports",
+            |params, scope| {
+                let names = params.iter().map(|i| i.get_string());
+                scope
+                    .0
+                    .borrow_mut()
+                    .exports
+                    .get_or_insert_default()
+                    .extend(names);
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "strict",
+            Arity::Exact(0),
+            "Raise type errors instead of converting values in the rest of the scope",
+            |_, scope| {
+                // Pragma takes effect on the rest of the scope where it's declared
+                scope.0.borrow_mut().strict = true;
+                Ok(Type::Null)
+            },
+        ),
+        builtin(
+            "doc",
+            Arity::Exact(1),
+            "Documentation of the builtin function",
+            |params, scope| match params[0].eval(scope)? {
+                Type::Function(Function::BuiltIn(func)) => Ok(Type::String(format!(
+                    "({}) takes {}\n{}",
+                    func.name, func.arity, func.doc
+                ))),
+                other => Err(other.mismatch("builtin function")),
+            },
+        ),
+        ("the".to_string(), THE.with(Type::clone)),
+        ("new-line".to_string(), Type::String("\n".to_string())),
+        ("double-quote".to_string(), Type::String("\"".to_string())),
+        ("tab".to_string(), Type::String("\t".to_string())),
+    ]))
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum LazoError {
+    #[error("Runtime Error! {0}")]
+    Runtime(String),
+
+    #[error("Syntax Error! {0}")]
+    Syntax(String),
+
+    #[error("Type Error! {0}")]
+    Type(String),
+
+    #[error("Arity Error! `{0}` takes {2}, but {1} passed")]
+    Arity(String, usize, Arity),
+
+    #[error("Unbound Variable Error! `{0}` is not defined{hint}", hint = match .1 {
+        Some(similar) => format!(", did you mean `{similar}`?"),
+        None => String::new(),
+    })]
+    UnboundVariable(String, Option<String>),
+
+    #[error("IO Error! {0}")]
+    Io(String),
+
+    /// Value that no pattern of `match` or the parameter matches
+    #[error("Match Error! there's no pattern that matches `{0:?}`")]
+    Match(Type),
+
+    #[error("Fuel Error! evaluation ran out of the fuel, that's {0} steps")]
+    Fuel(u64),
+
+    #[error("Timeout Error! evaluation took longer than {0:?}")]
+    Timeout(Duration),
+
+    #[error("Recursion Error! function calls or forced thunks are nested deeper than {0}")]
+    Recursion(usize),
+
+    #[error("Size Error! {0} is longer than {1}")]
+    Size(String, usize),
+
+    #[error("Capability Error! `{0}` is not allowed in this sandbox")]
+    Denied(String),
+
+    /// Error that's raised by the program with its kind and payload
+    #[error("{0} Error! {message}", message = .1.get_string())]
+    Raised(String, Type),
+
+    #[error("{0}\n{1}")]
+    Located(Box<LazoError>, Span),
+
+    #[error("{0}{trace}", trace = backtrace(.0, .1))]
+    Traced(Box<LazoError>, Vec<Frame>, usize),
+}
+
+impl LazoError {
+    /// Point out where the error occurred, unless it's already pointed out
+    fn at(self, span: Option<Span>) -> LazoError {
+        match (self, span) {
+            (LazoError::Traced(err, mut trace, exited), span) => {
+                // Calls made by builtins have no span, so it's where the error passes next
+                let index = trace.len() - exited;
+                if let Some(frame) = trace.get_mut(index) {
+                    if frame.call.is_none() {
+                        frame.call = span.clone();
+                    }
+                }
+                LazoError::Traced(Box::new(err.at(span)), trace, exited)
+            }
+            // Unbound variable in the argument is found when it's forced in the other code,
+            // so it's pointed out again where the name is written
+            (LazoError::Located(err, old), Some(span)) => match err.as_ref() {
+                LazoError::UnboundVariable(name, _) if old.text() != name => {
+                    let token = span.token(name).unwrap_or(old);
+                    LazoError::Located(err, token)
+                }
+                _ => LazoError::Located(err, old),
+            },
+            (err, None) => err,
+            (err, Some(span)) => {
+                let span = match &err {
+                    LazoError::UnboundVariable(name, _) => span.token(name).unwrap_or(span),
+                    _ => span,
+                };
+                LazoError::Located(Box::new(err), span)
+            }
+        }
+    }
+
+    /// Attach the call stack when the error goes out of the innermost function call,
+    /// and count how many calls it has gone out of after that
+    fn traced(self, runtime: &Runtime) -> LazoError {
+        match self {
+            LazoError::Traced(err, trace, exited) => LazoError::Traced(err, trace, exited + 1),
+            err => LazoError::Traced(Box::new(err), runtime.call_stack.borrow().clone(), 1),
+        }
+    }
+
+    fn span(&self) -> Option<&Span> {
+        match self {
+            LazoError::Located(_, span) => Some(span),
+            LazoError::Traced(err, ..) => err.span(),
+            _ => None,
+        }
+    }
+
+    /// Kind of the error that `error-kind` returns
+    pub fn kind(&self) -> String {
+        match self {
+            LazoError::Runtime(_) => "runtime".to_string(),
+            LazoError::Syntax(_) => "syntax".to_string(),
+            LazoError::Type(_) => "type".to_string(),
+            LazoError::Arity(..) => "arity".to_string(),
+            LazoError::UnboundVariable(..) => "unbound-variable".to_string(),
+            LazoError::Io(_) => "io".to_string(),
+            LazoError::Match(_) => "match".to_string(),
+            LazoError::Fuel(_) => "fuel".to_string(),
+            LazoError::Timeout(_) => "timeout".to_string(),
+            LazoError::Recursion(_) => "recursion".to_string(),
+            LazoError::Size(..) => "size".to_string(),
+            LazoError::Denied(_) => "capability".to_string(),
+            LazoError::Raised(kind, _) => kind.to_owned(),
+            LazoError::Located(err, _) | LazoError::Traced(err, ..) => err.kind(),
+        }
+    }
+
+    /// Message of the error without the location
+    pub fn message(&self) -> String {
+        match self {
+            LazoError::Runtime(message)
+            | LazoError::Syntax(message)
+            | LazoError::Type(message)
+            | LazoError::Io(message) => message.to_owned(),
+            LazoError::Raised(_, payload) => payload.get_string(),
+            LazoError::Located(err, _) | LazoError::Traced(err, ..) => err.message(),
+            err => {
+                let text = err.to_string();
+                text.split_once("! ")
+                    .map(|(_, i)| i.to_string())
+                    .unwrap_or(text)
+            }
+        }
+    }
+
+    /// Whether it's the violation of the sandbox, that the program can't catch
+    pub fn is_limit(&self) -> bool {
+        match self {
+            LazoError::Fuel(_)
+            | LazoError::Timeout(_)
+            | LazoError::Recursion(_)
+            | LazoError::Size(..)
+            | LazoError::Denied(_) => true,
+            LazoError::Located(err, _) | LazoError::Traced(err, ..) => err.is_limit(),
+            _ => false,
+        }
+    }
+
+    /// Value that the error carries, it's the message unless it's raised with the payload
+    pub fn payload(&self) -> Type {
+        match self {
+            LazoError::Raised(_, payload) | LazoError::Match(payload) => payload.to_owned(),
+            LazoError::Located(err, _) | LazoError::Traced(err, ..) => err.payload(),
+            err => Type::String(err.message()),
+        }
+    }
+}
+
+/// Function call that's in progress
+#[derive(Clone, Debug)]
+pub struct Frame {
+    name: String,
+    call: Option<Span>,
+}
+
+fn backtrace(err: &LazoError, trace: &[Frame]) -> String {
+    let position = |span: Option<&Span>| match span {
+        Some(span) => format!("{}:{}", span.source.name, span.line_col().0),
+        None => "unknown".to_string(),
+    };
+
+    // Each frame is where the error is, or where the inner function is called
+    let mut result = String::new();
+    let mut location = err.span();
+    for frame in trace.iter().rev() {
+        result += &format!("\n    at {} ({})", frame.name, position(location));
+        location = frame.call.as_ref();
+    }
+    if !trace.is_empty() {
+        result += &format!("\n    at <toplevel> ({})", position(location));
+    }
+    result
+}
+
+/// Source code that is referred by spans to report errors
+struct Source {
+    name: String,
+    code: String,
+}
+
+/// Range of the source code where a token or expression comes from
+#[derive(Clone)]
+pub struct Span {
+    source: Rc<Source>,
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    fn new(name: String, code: String) -> Span {
+        let end = code.len();
+        Span {
+            source: Rc::new(Source { name, code }),
+            start: 0,
+            end,
+        }
+    }
+
+    fn slice(&self, start: usize, end: usize) -> Span {
+        Span {
+            source: self.source.clone(),
+            start,
+            end,
+        }
+    }
+
+    /// Span without the outer brackets or quotes
+    fn inner(&self) -> Span {
+        self.slice(self.start + 1, self.end - 1)
+    }
+
+    fn text(&self) -> &str {
+        &self.source.code[self.start..self.end]
+    }
+
+    /// Token of the name in the span, that's searched into the nested expressions in order.
+    /// Quoted code isn't searched, as the names in it aren't variables
+    fn token(&self, name: &str) -> Option<Span> {
+        let text = self.text();
+        if text == name {
+            return Some(self.clone());
+        }
+        let prefix = text.len() - text.trim_start_matches(['`', ',', '@']).len();
+        if prefix > 0 {
+            return self.slice(self.start + prefix, self.end).token(name);
+        }
+        match text.chars().next() {
+            Some('(' | '[' | '{') => {
+                let tokens = tokenize(self.inner()).ok()?;
+                if tokens.first().is_some_and(|head| head.text() == "quote") {
+                    return None;
+                }
+                tokens.iter().find_map(|token| token.token(name))
+            }
+            _ => None,
+        }
+    }
+
+    /// Last token in the expression
+    fn last(&self) -> Option<Span> {
+        tokenize(self.inner()).ok()?.pop()
+    }
+
+    /// Line and column number, counted from 1
+    fn line_col(&self) -> (usize, usize) {
+        let before = &self.source.code[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let col = before[before.rfind('\n').map(|i| i + 1).unwrap_or(0)..]
+            .chars()
+            .count()
+            + 1;
+        (line, col)
+    }
+}
+
+impl Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, col) = self.line_col();
+        write!(f, "{}:{line}:{col}", self.source.name)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, _) = self.line_col();
+        let code = &self.source.code;
+        let line_start = code[..self.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = code[self.start..]
+            .find('\n')
+            .map(|i| self.start + i)
+            .unwrap_or(code.len());
+        let source_line = code[line_start..line_end].trim_end_matches('\r');
+
+        // Keeping tabs so that the caret is aligned with the code above
+        let indent: String = code[line_start..self.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = code[self.start..self.end.min(line_end).max(self.start)]
+            .chars()
+            .count()
+            .max(1);
+
+        let gutter = " ".repeat(line.to_string().len());
+        writeln!(f, "{gutter}--> {self:?}")?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line} | {source_line}")?;
+        write!(f, "{gutter} | {indent}{}", "^".repeat(width))
+    }
+}
+
+/// Variables of a block, chained to the enclosing one
+#[derive(Clone, Default)]
+pub struct Scope(Rc<RefCell<Env>>);
+
+#[derive(Default)]
+struct Env {
+    vars: HashMap<String, Type>,
+    /// Names of the variables that compiled code finds by the index, see `Compiler`
+    layout: Rc<[String]>,
+    /// Values of the variables in the layout, that's `None` until it's defined
+    slots: Vec<Option<Type>>,
+    parent: Option<Scope>,
+    runtime: Rc<Runtime>,
+    /// Script file of the module, that's set on its top-level scope
+    file: Option<PathBuf>,
+    /// Names that the module exports, or all of the definitions if it's not specified
+    exports: Option<Vec<String>>,
+    /// Whether `(strict)` is declared in the scope
+    strict: bool,
+}
+
+/// State of the interpreter that's shared by every scope
+#[derive(Default)]
+struct Runtime {
+    call_stack: RefCell<Vec<Frame>>,
+    gensym: Cell<usize>,
+    /// Exports of the modules that're already loaded
+    modules: RefCell<HashMap<PathBuf, HashMap<String, Type>>>,
+    /// Modules that're being loaded, to find circular import
+    importing: RefCell<Vec<PathBuf>>,
+    /// Strict mode for all of the modules
+    strict: Cell<bool>,
+    engine: Cell<Engine>,
+    limits: Cell<Limits>,
+    /// Steps that're evaluated, and when it should finish, since the interpreter started running
+    steps: Cell<u64>,
+    deadline: Cell<Option<Instant>>,
+    /// Thunks that're being forced one inside another
+    forcing: Cell<usize>,
+    /// Capabilities that the host doesn't allow the program to use
+    denied: RefCell<HashSet<Capability>>,
+    /// Names of the record types that're defined, for the checker of the later code
+    records: RefCell<HashSet<String>>,
+}
+
+/// Budget of the execution for the untrusted code, each of them is unlimited if it's `None`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// Number of the function calls that're evaluated
+    pub fuel: Option<u64>,
+    /// Time that the evaluation can take
+    pub timeout: Option<Duration>,
+    /// Depth of the nested function calls, and of the thunks that force one another
+    pub depth: Option<usize>,
+    /// Elements of nested lists, maps and records, length of strings that builtins make,
+    /// and bits of the exact numbers
+    pub size: Option<usize>,
+}
+
+/// Side effect of builtins that the host can forbid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// `exit` that stops the host process
+    Exit,
+    /// `input` that waits for standard input
+    Input,
+    /// `print` and `debug` that write to standard output
+    Print,
+    /// `import` that reads files
+    Io,
+}
+
+/// How the code is executed, both of them give the same result
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Engine {
+    /// Bytecode VM that runs the compiled code
+    #[default]
+    Vm,
+    /// Evaluating the code as it is, that's the reference of the VM
+    TreeWalker,
+}
+
+impl Runtime {
+    /// Fresh name that never conflicts with the user's one
+    fn gensym(&self, name: &str) -> String {
+        self.gensym.set(self.gensym.get() + 1);
+        format!("{name}#{}", self.gensym.get())
+    }
+
+    /// Reset the budget when the interpreter starts running the code
+    fn start(&self) {
+        self.steps.set(0);
+        let timeout = self.limits.get().timeout;
+        self.deadline
+            .set(timeout.map(|timeout| Instant::now() + timeout));
+    }
+
+    /// Count the step of the evaluation, that fails when it's over the budget
+    fn tick(&self) -> Result<(), LazoError> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        let limits = self.limits.get();
+        if let Some(fuel) = limits.fuel.filter(|fuel| steps > *fuel) {
+            return Err(LazoError::Fuel(fuel));
+        }
+        // Reading the clock is slow, so it's checked once in a while
+        match (self.deadline.get(), limits.timeout) {
+            (Some(deadline), Some(timeout))
+                if steps.is_multiple_of(1024) && Instant::now() > deadline =>
+            {
+                Err(LazoError::Timeout(timeout))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluate the thunk that's forced, it's nested like the function call
+    fn force<T>(&self, eval: impl FnOnce() -> Result<T, LazoError>) -> Result<T, LazoError> {
+        let depth = self.forcing.get();
+        if let Some(limit) = self.limits.get().depth.filter(|limit| depth >= *limit) {
+            return Err(LazoError::Recursion(limit));
+        }
+        self.forcing.set(depth + 1);
+        let result = eval();
+        self.forcing.set(depth);
+        result
+    }
+
+    /// Raise error if the list or the string of the length is over the limit
+    fn check_len(&self, kind: &str, len: usize) -> Result<(), LazoError> {
+        match self.limits.get().size {
+            Some(size) if len > size => Err(LazoError::Size(kind.to_string(), size)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_size(&self, value: &Type) -> Result<(), LazoError> {
+        match value {
+            // Elements of the nested values are counted together, so that they can't be nested
+            // so deep that printing, comparing or dropping them overflows the stack
+            Type::List(_) | Type::Expr(..) | Type::Map(_) | Type::Record(_) => {
+                match self.limits.get().size {
+                    Some(size) => self.check_len(&value.get_type(), value.count(size)),
+                    None => Ok(()),
+                }
+            }
+            Type::String(text) if self.limits.get().size.is_some() => {
+                self.check_len("string", text.chars().count())
+            }
+            Type::Number(number) => self.check_len("number", number.bits()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Builtin of the name can be used only if the host allows the capability
+    fn require(&self, capability: Capability, name: &str) -> Result<(), LazoError> {
+        if self.denied.borrow().contains(&capability) {
+            Err(LazoError::Denied(name.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+thread_local! {
+    /// Values of the scopes that're dropped while another scope is being dropped, see `Drop for Scope`
+    static DROPPED: RefCell<Option<Vec<Type>>> = const { RefCell::new(None) };
+}
+
+/// Long chain of the scopes and the thunks that capture them is dropped in the loop,
+/// the recursion would overflow the stack
+impl Drop for Scope {
+    fn drop(&mut self) {
+        // Only the last owner drops the variables
+        if Rc::strong_count(&self.0) > 1 {
+            return;
+        }
+        let Ok(mut env) = self.0.try_borrow_mut() else {
+            return;
+        };
+        let slots = std::mem::take(&mut env.slots);
+        let vars = std::mem::take(&mut env.vars);
+        drop(env);
+        let outermost = DROPPED.with_borrow_mut(|dropped| match dropped {
+            Some(_) => false,
+            None => {
+                *dropped = Some(vec![]);
+                true
+            }
+        });
+        if !outermost {
+            DROPPED.with_borrow_mut(|dropped| {
+                let values = dropped.get_or_insert_default();
+                values.extend(slots.into_iter().flatten());
+                values.extend(vars.into_values());
+            });
+            return;
+        }
+        // The outermost one drops the values that inner scopes leave
+        drop((slots, vars));
+        while let Some(values) = DROPPED.with_borrow_mut(|dropped| {
+            dropped
+                .as_mut()
+                .map(std::mem::take)
+                .filter(|values| !values.is_empty())
+        }) {
+            drop(values);
+        }
+        DROPPED.set(None);
+    }
+}
+
+impl Scope {
+    fn new(parent: &Scope) -> Scope {
+        Scope(Rc::new(RefCell::new(Env {
+            parent: Some(parent.clone()),
+            runtime: parent.runtime(),
+            ..Env::default()
+        })))
+    }
+
+    /// Scope of the compiled code, whose variables in the layout are stored in the slots
+    fn with_layout(parent: &Scope, layout: Rc<[String]>) -> Scope {
+        let slots = vec![None; layout.len()];
+        Scope(Rc::new(RefCell::new(Env {
+            layout,
+            slots,
+            parent: Some(parent.clone()),
+            runtime: parent.runtime(),
+            ..Env::default()
+        })))
+    }
+
+    /// Top-level scope of another module, that's isolated but shares the runtime
+    fn module(&self, file: PathBuf) -> Scope {
+        let builtins = stdlib();
+        builtins.0.borrow_mut().runtime = self.runtime();
+        let module = Scope::new(&builtins);
+        module.0.borrow_mut().file = Some(file);
+        module
+    }
+
+    /// Whether values of wrong type raise error instead of being converted
+    fn is_strict(&self) -> bool {
+        let env = self.0.borrow();
+        env.strict || env.runtime.strict.get() || env.parent.as_ref().is_some_and(Scope::is_strict)
+    }
+
+    /// Script file of the module that the scope belongs to
+    fn file(&self) -> Option<PathBuf> {
+        let env = self.0.borrow();
+        match (&env.file, &env.parent) {
+            (Some(file), _) => Some(file.clone()),
+            (None, Some(parent)) => parent.file(),
+            (None, None) => None,
+        }
+    }
+
+    fn runtime(&self) -> Rc<Runtime> {
+        self.0.borrow().runtime.clone()
+    }
+
+    fn get(&self, name: &str) -> Option<Type> {
+        let env = self.0.borrow();
+        if let Some(Some(value)) = env.position(name).map(|i| &env.slots[i]) {
+            Some(value.clone())
+        } else if let Some(value) = env.vars.get(name) {
+            Some(value.clone())
+        } else if let Some(parent) = &env.parent {
+            parent.get(name)
+        } else {
+            None
+        }
+    }
+
+    /// Variable at the slot that's resolved by the compiler, it's the same as `Scope::get`
+    /// unless the variable is shadowed by the one that's defined dynamically like by `eval`
+    fn slot(&self, depth: usize, index: usize, name: &str) -> Option<Type> {
+        let env = self.0.borrow();
+        if depth > 0 {
+            if let Some(value) = env.vars.get(name) {
+                return Some(value.clone());
+            }
+            env.parent.as_ref()?.slot(depth - 1, index, name)
+        } else if let Some(Some(value)) = env.slots.get(index) {
+            Some(value.clone())
+        } else {
+            drop(env);
+            self.get(name)
+        }
+    }
+
+    fn insert(&self, name: String, value: Type) {
+        let mut env = self.0.borrow_mut();
+        match env.position(&name) {
+            Some(index) => env.slots[index] = Some(value),
+            None => {
+                env.vars.insert(name, value);
+            }
+        }
+    }
+
+    /// Overwrite the variable that's defined in the nearest scope
+    fn set(&self, name: &str, value: Type) -> Result<(), LazoError> {
+        let mut scope = self.clone();
+        loop {
+            let parent = {
+                let mut env = scope.0.borrow_mut();
+                if let Some(index) = env.position(name).filter(|i| env.slots[*i].is_some()) {
+                    env.slots[index] = Some(value);
+                    return Ok(());
+                }
+                if let Some(var) = env.vars.get_mut(name) {
+                    *var = value;
+                    return Ok(());
+                }
+                env.parent.clone()
+            };
+            match parent {
+                Some(parent) => scope = parent,
+                None => return Err(self.unbound(name)),
+            }
+        }
+    }
+
+    /// Names of all the variables that can be seen from the scope
+    fn names(&self) -> Vec<String> {
+        let env = self.0.borrow();
+        let mut names: Vec<String> = env.vars.keys().cloned().collect();
+        let slots = env.layout.iter().zip(&env.slots);
+        names.extend(
+            slots
+                .filter(|(_, v)| v.is_some())
+                .map(|(name, _)| name.clone()),
+        );
+        if let Some(parent) = &env.parent {
+            names.extend(parent.names());
+        }
+        names
+    }
+
+    /// Error for the variable that's not defined, with the most similar name in the scope
+    fn unbound(&self, name: &str) -> LazoError {
+        // Too short name is similar to everything
+        let limit = name.chars().count() / 3;
+        let similar = self
+            .names()
+            .into_iter()
+            // Renamed variables of macros are hidden from the user
+            .filter(|candidate| !candidate.contains('#'))
+            .map(|candidate| (edit_distance(name, &candidate), candidate))
+            .filter(|(distance, _)| (1..=limit).contains(distance))
+            .min();
+        LazoError::UnboundVariable(name.to_string(), similar.map(|(_, name)| name))
+    }
+}
+
+impl Env {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.layout.iter().position(|i| i == name)
+    }
+}
+
+impl From<HashMap<String, Type>> for Scope {
+    fn from(vars: HashMap<String, Type>) -> Scope {
+        Scope(Rc::new(RefCell::new(Env {
+            vars,
+            ..Env::default()
+        })))
+    }
+}
+
+#[derive(Clone)]
+pub enum Type {
+    Function(Function),
+    Expr(Vec<Type>, Option<Span>),
+    List(Vec<Type>),
+    /// Lazy sequence, that's realized as the list when it's needed
+    Stream(Stream),
+    /// Value of the type that's defined by `define-record`
+    Record(Rc<Record>),
+    /// Pairs of the key and the value, that're found by the key's representation
+    Map(BTreeMap<String, (Type, Type)>),
+    Symbol(String),
+    Number(Number),
+    String(String),
+    Bool(bool),
+    Thunk(Thunk),
+    /// Error that's caught by `try`
+    Error(Rc<LazoError>),
+    /// Code in tail position, that's never seen outside of `Type::eval`
+    #[doc(hidden)]
+    Tail(Box<Type>, Scope),
+    Null,
+}
+
+#[derive(Clone)]
+pub enum Function {
+    BuiltIn(Rc<BuiltIn>),
+    UserDefined(Rc<Closure>),
+    Macro(Rc<Macro>),
+}
+
+impl Function {
+    /// Whether both are the same function, not only they have the same code
+    fn is(&self, other: &Function) -> bool {
+        match (self, other) {
+            (Function::BuiltIn(a), Function::BuiltIn(b)) => Rc::ptr_eq(a, b),
+            (Function::UserDefined(a), Function::UserDefined(b)) => Rc::ptr_eq(a, b),
+            (Function::Macro(a), Function::Macro(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Function implemented in Rust, it takes the arguments as code to evaluate them by itself
+pub struct BuiltIn {
+    name: String,
+    arity: Arity,
+    doc: String,
+    func: Box<Native>,
+}
+
+/// Code of the builtin, that gets the arguments unevaluated
+type Native = dyn Fn(Vec<Type>, &mut Scope) -> Result<Type, LazoError>;
+
+/// How many arguments the function takes
+#[derive(Clone, Copy, Debug)]
+pub enum Arity {
+    Exact(usize),
+    /// Optional arguments are allowed up to the maximum
+    Range(usize, usize),
+    /// Rest of the arguments are variadic
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exact(n) => count == n,
+            Arity::Range(min, max) => (min..=max).contains(&count),
+            Arity::AtLeast(min) => count >= min,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Exact(1) => write!(f, "1 argument"),
+            Arity::Exact(n) => write!(f, "{n} arguments"),
+            Arity::Range(min, max) => write!(f, "{min} to {max} arguments"),
+            Arity::AtLeast(min) => write!(f, "at least {min} arguments"),
+        }
+    }
+}
+
+/// User-defined function with the environment where it's made
+pub struct Closure {
+    name: Option<String>,
+    args: Vec<Param>,
+    ret: Option<String>,
+    body: Vec<Type>,
+    env: Scope,
+    /// Bytecode of the body, that's compiled when it's called by the VM at first
+    code: Rc<OnceCell<Rc<Code>>>,
+}
+
+impl Closure {
+    fn new(
+        name: Option<String>,
+        args: &[Type],
+        body: &[Type],
+        env: &Scope,
+    ) -> Result<Closure, LazoError> {
+        let ((args, ret), body) = signature(args, body)?;
+        Ok(Closure {
+            name,
+            args,
+            ret,
+            body: body.to_vec(),
+            env: env.clone(),
+            code: Rc::default(),
+        })
+    }
+
+    fn name(&self) -> String {
+        self.name.clone().unwrap_or("lambda".to_string())
+    }
+
+    fn code(&self) -> Rc<Code> {
+        self.code
+            .get_or_init(|| Compiler::function(&self.args, &self.body, &self.env))
+            .clone()
+    }
+
+    /// Scope of the function's code, where the arguments are set to the slots
+    fn bind(&self, code: &Code, args: Vec<Type>) -> Scope {
+        let scope = Scope::with_layout(&self.env, code.layout.clone());
+        let mut env = scope.0.borrow_mut();
+        for (index, value) in code.params.iter().zip(args) {
+            env.slots[*index] = Some(value);
+        }
+        drop(env);
+        scope
+    }
+
+    /// Run the compiled code with the arguments that're already suspended,
+    /// the frame of the call is recorded at the depth of the call stack
+    fn enter(&self, args: Vec<Type>, span: &Option<Span>, depth: usize) -> Result<Type, LazoError> {
+        let code = self.code();
+        let scope = self.bind(&code, args);
+        let runtime = scope.runtime();
+        enter_frame(&runtime, depth, self.name(), span)?;
+        match &self.ret {
+            // Returned value is needed to check it, so it's not a tail call
+            Some(ret) => {
+                let depth = depth + 1;
+                let value = settle(Machine::new(code, scope, depth).run(), depth, &runtime)?;
+                self.check(ret, value)
+            }
+            None => Machine::new(code, scope, depth).run(),
+        }
+    }
+
+    fn check(&self, ret: &str, value: Type) -> Result<Type, LazoError> {
+        if value.has_type(ret) {
+            Ok(value)
+        } else {
+            Err(LazoError::Type(format!(
+                "`{}` should return {ret}, but returned {} `{value:?}`",
+                self.name(),
+                value.get_type()
+            )))
+        }
+    }
+}
+
+/// Argument that's checked by the type annotation when it's evaluated.
+/// The check is deferred like the evaluation, so the argument that the function
+/// never uses isn't checked, as `(f "s")` to `(define (f (x : number)) 1)` returns 1
+fn checked(annotation: &Option<String>, arg: &Type, span: &Option<Span>) -> Option<Type> {
+    match annotation {
+        Some(annotation) if annotation != "any" => Some(Type::Expr(
+            vec![
+                THE.with(Type::clone),
+                Type::Symbol(annotation.clone()),
+                arg.clone(),
+            ],
+            arg.span().or(span.clone()),
+        )),
+        _ => None,
+    }
+}
+
+/// Recording the call to report where errors come from,
+/// tail call replaces the frame of the function that calls it
+fn enter_frame(
+    runtime: &Runtime,
+    depth: usize,
+    name: String,
+    call: &Option<Span>,
+) -> Result<(), LazoError> {
+    if let Some(limit) = runtime.limits.get().depth.filter(|limit| depth >= *limit) {
+        return Err(LazoError::Recursion(limit));
+    }
+    let mut call_stack = runtime.call_stack.borrow_mut();
+    call_stack.truncate(depth);
+    call_stack.push(Frame {
+        name,
+        call: call.clone(),
+    });
+    Ok(())
+}
+
+/// Finish the evaluation that's started at the depth of the call stack, as `Type::eval` does
+fn settle(
+    result: Result<Type, LazoError>,
+    depth: usize,
+    runtime: &Runtime,
+) -> Result<Type, LazoError> {
+    match result {
+        Ok(Type::Tail(expr, mut scope)) => expr.eval_in(&mut scope, depth),
+        // Leaving the function call that's made in the evaluation
+        result if runtime.call_stack.borrow().len() > depth => {
+            let result = result.map_err(|err| err.traced(runtime));
+            runtime.call_stack.borrow_mut().truncate(depth);
+            result
+        }
+        result => result,
+    }
+}
+
+/// Macro that's defined by `syntax-rules`, it's expanded before evaluation
+pub struct Macro {
+    literals: Vec<String>,
+    rules: Vec<(Type, Type)>,
+    /// Scope where the macro is defined, that free variables of the templates refer to
+    env: Scope,
+}
+
+/// Code that's matched to the pattern variable, it's repeated when followed by ellipsis
+#[derive(Clone)]
+enum Matched {
+    One(Type),
+    Many(Vec<Matched>),
+}
+
+const ELLIPSIS: &str = "...";
+
+impl Macro {
+    /// Expand the macro call, `shadowed` tells whether the variable of the value
+    /// that's defined where the macro is defined is shadowed where it's called
+    fn expand(
+        &self,
+        form: &[Type],
+        shadowed: impl Fn(&str, &Type) -> bool,
+    ) -> Result<Type, LazoError> {
+        for (pattern, template) in &self.rules {
+            // The keyword of the macro isn't matched
+            let pattern = pattern.get_list();
+            let mut binds = HashMap::new();
+            if self.match_seq(&pattern[1.min(pattern.len())..], &form[1..], &mut binds) {
+                // Variables that the template introduces are renamed for hygiene,
+                // so that they never capture nor shadow the user's ones
+                let mut renames = HashMap::new();
+                for name in Macro::binders(template)? {
+                    if !binds.contains_key(&name) && !renames.contains_key(&name) {
+                        let rename = self.env.runtime().gensym(&name);
+                        renames.insert(name, Type::Symbol(rename));
+                    }
+                }
+                // Other variables refer to the definition of the macro's scope,
+                // its value is put in place of the one that the caller shadows
+                for name in Macro::symbols(template) {
+                    if binds.contains_key(&name) || renames.contains_key(&name) {
+                        continue;
+                    }
+                    let value = match self.env.get(&name).filter(|i| shadowed(&name, i)) {
+                        // Code can't refer to the thunk, so it's forced here
+                        Some(Type::Thunk(thunk)) => thunk.force()?,
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    renames.insert(name, value.quote());
+                }
+                return Macro::substitute(template, &binds, &renames);
+            }
+        }
+        Err(LazoError::Syntax(format!(
+            "there's no syntax rule that matches `{:?}`",
+            Type::Expr(form.to_vec(), None)
+        )))
+    }
+
+    fn match_one(&self, pattern: &Type, form: &Type, binds: &mut HashMap<String, Matched>) -> bool {
+        match pattern {
+            Type::Symbol(name) if name == "_" => true,
+            Type::Symbol(name) if self.literals.contains(name) => {
+                matches!(form, Type::Symbol(symbol) if symbol == name)
+            }
+            Type::Symbol(name) => {
+                binds.insert(name.to_owned(), Matched::One(form.to_owned()));
+                true
+            }
+            Type::Expr(patterns, _) => match form {
+                Type::Expr(forms, _) => self.match_seq(patterns, forms, binds),
+                _ => false,
+            },
+            Type::List(patterns) => match form {
+                Type::List(forms) => self.match_seq(patterns, forms, binds),
+                _ => false,
+            },
+            literal => literal == form,
+        }
+    }
+
+    fn match_seq(
+        &self,
+        patterns: &[Type],
+        forms: &[Type],
+        binds: &mut HashMap<String, Matched>,
+    ) -> bool {
+        let ellipsis = patterns
+            .iter()
+            .position(|i| matches!(i, Type::Symbol(name) if name == ELLIPSIS));
+        let Some(ellipsis) = ellipsis.filter(|i| *i > 0) else {
+            return patterns.len() == forms.len()
+                && patterns
+                    .iter()
+                    .zip(forms)
+                    .all(|(pattern, form)| self.match_one(pattern, form, binds));
+        };
+
+        // Pattern followed by ellipsis matches to any number of forms
+        let (before, after) = (&patterns[..ellipsis - 1], &patterns[ellipsis + 1..]);
+        if forms.len() < before.len() + after.len() {
+            return false;
+        }
+        let repeated = forms.len() - before.len() - after.len();
+        if !self.match_seq(before, &forms[..before.len()], binds)
+            || !self.match_seq(after, &forms[before.len() + repeated..], binds)
+        {
+            return false;
+        }
+        let mut matches = vec![];
+        for form in &forms[before.len()..before.len() + repeated] {
+            let mut inner = HashMap::new();
+            if !self.match_one(&patterns[ellipsis - 1], form, &mut inner) {
+                return false;
+            }
+            matches.push(inner);
+        }
+        for name in Macro::symbols(&patterns[ellipsis - 1]) {
+            if name != "_" && name != ELLIPSIS && !self.literals.contains(&name) {
+                let many = matches
+                    .iter()
+                    .filter_map(|i| i.get(&name).cloned())
+                    .collect();
+                binds.insert(name, Matched::Many(many));
+            }
+        }
+        true
+    }
+
+    fn substitute(
+        template: &Type,
+        binds: &HashMap<String, Matched>,
+        renames: &HashMap<String, Type>,
+    ) -> Result<Type, LazoError> {
+        Ok(match template {
+            // Symbols in the quoted data aren't variables
+            Type::Expr(templates, span) if matches!(templates.first(), Some(Type::Symbol(quote)) if quote == "quote") =>
+            {
+                let renames = renames
+                    .iter()
+                    .filter(|(_, rename)| matches!(rename, Type::Symbol(_)))
+                    .map(|(name, rename)| (name.to_owned(), rename.to_owned()))
+                    .collect();
+                Type::Expr(
+                    Macro::substitute_seq(templates, binds, &renames)?,
+                    span.clone(),
+                )
+            }
+            Type::Symbol(name) => match binds.get(name) {
+                Some(Matched::One(form)) => form.to_owned(),
+                Some(Matched::Many(_)) => {
+                    return Err(LazoError::Syntax(format!(
+                        "pattern variable `{name}` should be followed by ellipsis"
+                    )))
+                }
+                None => renames
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(Type::Symbol(name.to_owned())),
+            },
+            Type::Expr(templates, span) => Type::Expr(
+                Macro::substitute_seq(templates, binds, renames)?,
+                span.clone(),
+            ),
+            Type::List(templates) => Type::List(Macro::substitute_seq(templates, binds, renames)?),
+            other => other.to_owned(),
+        })
+    }
+
+    fn substitute_seq(
+        templates: &[Type],
+        binds: &HashMap<String, Matched>,
+        renames: &HashMap<String, Type>,
+    ) -> Result<Vec<Type>, LazoError> {
+        let mut result = vec![];
+        let mut templates = templates.iter().peekable();
+        while let Some(template) = templates.next() {
+            if !matches!(templates.peek(), Some(Type::Symbol(name)) if name == ELLIPSIS) {
+                result.push(Macro::substitute(template, binds, renames)?);
+                continue;
+            }
+            templates.next();
+
+            // Template followed by ellipsis is repeated for each of the matched forms
+            let repeated: Vec<(String, Vec<Matched>)> = Macro::symbols(template)
+                .into_iter()
+                .filter_map(|name| match binds.get(&name) {
+                    Some(Matched::Many(many)) => Some((name, many.to_owned())),
+                    _ => None,
+                })
+                .collect();
+            let Some((_, first)) = repeated.first() else {
+                return Err(LazoError::Syntax(format!(
+                    "there's no pattern variable to repeat in `{template:?}`"
+                )));
+            };
+            if repeated.iter().any(|(_, many)| many.len() != first.len()) {
+                return Err(LazoError::Syntax(format!(
+                    "pattern variables in `{template:?}` are matched different times"
+                )));
+            }
+            for index in 0..first.len() {
+                let mut binds = binds.clone();
+                for (name, many) in &repeated {
+                    binds.insert(name.to_owned(), many[index].to_owned());
+                }
+                result.push(Macro::substitute(template, &binds, renames)?);
+            }
+        }
+        Ok(result)
+    }
+
+    fn symbols(code: &Type) -> Vec<String> {
+        match code {
+            Type::Symbol(name) => vec![name.to_owned()],
+            Type::Expr(list, _) | Type::List(list) => {
+                list.iter().flat_map(Macro::symbols).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Variables that are bound by `let`, `lambda` or `define` in the code
+    fn binders(code: &Type) -> Result<Vec<String>, LazoError> {
+        let Type::Expr(list, _) = code else {
+            return Ok(vec![]);
+        };
+        let mut result = vec![];
+        for i in list {
+            result.extend(Macro::binders(i)?);
+        }
+        let names = match list.as_slice() {
+            [Type::Symbol(form), bindings, ..]
+                if ["let", "let*", "letrec"].contains(&form.as_str()) =>
+            {
+                let mut names = vec![];
+                for binding in bindings.get_list() {
+                    match binding.get_list().first() {
+                        Some(name) => names.push(name.to_owned()),
+                        None => {
+                            return Err(LazoError::Syntax(format!(
+                                "binding should be pair of the name and the value, but provided `{binding:?}` is not"
+                            )))
+                        }
+                    }
+                }
+                names
+            }
+            [Type::Symbol(form), args, ..] if form == "lambda" => args.get_list(),
+            [Type::Symbol(form), Type::Symbol(name), ..] if form == "define" => {
+                vec![Type::Symbol(name.to_owned())]
+            }
+            [Type::Symbol(form), Type::Expr(signature, _), ..] if form == "define" => {
+                signature.to_owned()
+            }
+            [Type::Symbol(form), _, clauses @ ..] if form == "match" => clauses
+                .iter()
+                .filter_map(|i| clause(i).ok())
+                .map(|(pattern, ..)| pattern.to_owned())
+                .collect(),
+            _ => vec![],
+        };
+        for name in names {
+            // Annotated parameter like `(x : number)` binds `x`, and patterns bind their variables
+            let name = annotation(&name).map(|(name, _)| name).unwrap_or(name);
+            result.extend(pattern_vars(&name).unwrap_or_default());
+        }
+        Ok(result)
+    }
+}
+
+/// Suspended computation that is evaluated at most once
+#[derive(Clone)]
+pub struct Thunk(Rc<RefCell<Suspension>>);
+
+#[derive(Clone)]
+enum Suspension {
+    Delayed(Type, Scope),
+    /// Code that's compiled to be evaluated by the VM
+    Compiled(Rc<Code>, Scope),
+    Forcing,
+    Forced(Type),
+}
+
+impl Thunk {
+    fn new(expr: Type, scope: Scope) -> Thunk {
+        Thunk(Rc::new(RefCell::new(Suspension::Delayed(expr, scope))))
+    }
+
+    fn compiled(code: Rc<Code>, scope: Scope) -> Thunk {
+        Thunk(Rc::new(RefCell::new(Suspension::Compiled(code, scope))))
+    }
+
+    /// Thunk that's already evaluated, to bind a value that shouldn't be forced by loading
+    fn forced(value: Type) -> Thunk {
+        Thunk(Rc::new(RefCell::new(Suspension::Forced(value))))
+    }
+
+    fn force(&self) -> Result<Type, LazoError> {
+        // Thunks that it surely needs are forced from the deepest one, otherwise the chain
+        // that's made by the accumulator of the loop like `(* acc n)` overflows the stack
+        let mut chain: Vec<Thunk> = vec![];
+        let mut current = self.needed();
+        while let Some(thunk) = current {
+            if Rc::ptr_eq(&thunk.0, &self.0) || chain.iter().any(|i| Rc::ptr_eq(&i.0, &thunk.0)) {
+                break;
+            }
+            current = thunk.needed();
+            chain.push(thunk);
+        }
+        for thunk in chain.iter().rev() {
+            thunk.evaluate()?;
+        }
+        self.evaluate()
+    }
+
+    /// Thunk that isn't evaluated yet, and that's surely forced when this one is evaluated
+    fn needed(&self) -> Option<Thunk> {
+        match &*self.0.borrow() {
+            Suspension::Delayed(expr, scope) => expr.needed(scope),
+            Suspension::Compiled(code, scope) => code.source.needed(scope),
+            Suspension::Forcing | Suspension::Forced(_) => None,
+        }
+    }
+
+    fn evaluate(&self) -> Result<Type, LazoError> {
+        let state = self.0.replace(Suspension::Forcing);
+        match state {
+            Suspension::Delayed(expr, mut scope) => {
+                match scope.runtime().force(|| expr.eval(&mut scope)) {
+                    Ok(value) => {
+                        self.0.replace(Suspension::Forced(value.clone()));
+                        Ok(value)
+                    }
+                    Err(err) => {
+                        // Keep it suspended so that it can be forced again
+                        self.0.replace(Suspension::Delayed(expr, scope));
+                        Err(err)
+                    }
+                }
+            }
+            Suspension::Compiled(code, scope) => match scope
+                .runtime()
+                .force(|| Machine::eval(code.clone(), &scope))
+            {
+                Ok(value) => {
+                    self.0.replace(Suspension::Forced(value.clone()));
+                    Ok(value)
+                }
+                Err(err) => {
+                    self.0.replace(Suspension::Compiled(code, scope));
+                    Err(err)
+                }
+            },
+            Suspension::Forcing => Err(LazoError::Runtime(
+                "thunk depends on its own value".to_string(),
+            )),
+            Suspension::Forced(value) => {
+                self.0.replace(Suspension::Forced(value.clone()));
+                Ok(value)
+            }
+        }
+    }
+
+    fn value(&self) -> Option<Type> {
+        match &*self.0.borrow() {
+            Suspension::Forced(value) => Some(value.clone()),
+            Suspension::Delayed(..) | Suspension::Compiled(..) | Suspension::Forcing => None,
+        }
+    }
+
+    fn is_lazy(&self) -> bool {
+        match self.value() {
+            Some(Type::Thunk(inner)) => inner.is_lazy(),
+            Some(_) => false,
+            None => true,
+        }
+    }
+}
+
+/// Lazy sequence that makes its elements when they're needed, so it can be endless.
+/// Elements are made at most once, the rest of the stream is shared by its copies
+#[derive(Clone)]
+pub struct Stream {
+    node: Rc<RefCell<Node>>,
+    /// Scope where the functions of the stream are called
+    scope: Scope,
+}
+
+enum Node {
+    Pending(Generator),
+    Making,
+    Cons(Type, Stream),
+    Empty,
+}
+
+/// How the next element of the stream is made
+enum Generator {
+    /// Numbers from the current one by the step, it's endless without the end
+    Range(Number, Option<Number>, Number),
+    /// Function applied to the previous value
+    Iterate(Type, Type),
+    Items(Rc<Vec<Type>>, usize),
+    Cycle(Rc<Vec<Type>>, usize),
+    Map(Stream, Type),
+    Filter(Stream, Type),
+    Take(Stream, usize),
+    Drop(Stream, usize),
+    TakeWhile(Stream, Type),
+    Zip(Vec<Stream>),
+}
+
+impl Stream {
+    fn new(generator: Generator, scope: &Scope) -> Stream {
+        Stream::with(Node::Pending(generator), scope)
+    }
+
+    fn with(node: Node, scope: &Scope) -> Stream {
+        Stream {
+            node: Rc::new(RefCell::new(node)),
+            scope: scope.clone(),
+        }
+    }
+
+    /// Stream of the list's elements
+    fn items(list: Vec<Type>, scope: &Scope) -> Stream {
+        Stream::new(Generator::Items(Rc::new(list), 0), scope)
+    }
+
+    /// First element and the rest, or none if it's empty
+    fn next(&self) -> Result<Option<(Type, Stream)>, LazoError> {
+        let generator = match self.node.replace(Node::Making) {
+            Node::Pending(generator) => generator,
+            Node::Making => {
+                return Err(LazoError::Runtime(
+                    "stream depends on its own elements".to_string(),
+                ))
+            }
+            node => {
+                let next = match &node {
+                    Node::Cons(value, rest) => Some((value.clone(), rest.clone())),
+                    _ => None,
+                };
+                self.node.replace(node);
+                return Ok(next);
+            }
+        };
+        // Each element is the step, so that the budget stops the endless stream
+        match self
+            .scope
+            .runtime()
+            .tick()
+            .and_then(|_| self.make(&generator))
+        {
+            Ok(Some((value, rest))) => {
+                self.node.replace(Node::Cons(value.clone(), rest.clone()));
+                Ok(Some((value, rest)))
+            }
+            Ok(None) => {
+                self.node.replace(Node::Empty);
+                Ok(None)
+            }
+            Err(err) => {
+                // Keep it pending so that it can be made again
+                self.node.replace(Node::Pending(generator));
+                Err(err)
+            }
+        }
+    }
+
+    fn make(&self, generator: &Generator) -> Result<Option<(Type, Stream)>, LazoError> {
+        let rest = |generator| Stream::new(generator, &self.scope);
+        let call = |func: &Type, value: &Type| {
+            Type::Expr(vec![func.clone(), value.quote()], None).eval(&mut self.scope.clone())
+        };
+        Ok(match generator {
+            Generator::Range(current, end, step) => match end {
+                // It stops unless it's less than the end, like the end of `nan`
+                Some(end) if current.partial_cmp(end) != Some(Ordering::Less) => None,
+                _ => Some((
+                    Type::Number(current.clone()),
+                    rest(Generator::Range(current + step, end.clone(), step.clone())),
+                )),
+            },
+            Generator::Iterate(value, func) => {
+                let value = call(func, value)?;
+                Some((value.clone(), rest(Generator::Iterate(value, func.clone()))))
+            }
+            Generator::Items(list, index) => list.get(*index).map(|value| {
+                (
+                    value.clone(),
+                    rest(Generator::Items(list.clone(), index + 1)),
+                )
+            }),
+            Generator::Cycle(list, index) if !list.is_empty() => Some((
+                list[index % list.len()].clone(),
+                rest(Generator::Cycle(list.clone(), index + 1)),
+            )),
+            Generator::Cycle(..) => None,
+            Generator::Map(stream, func) => match stream.next()? {
+                Some((value, next)) => Some((
+                    call(func, &value)?,
+                    rest(Generator::Map(next, func.clone())),
+                )),
+                None => None,
+            },
+            Generator::Filter(stream, func) => {
+                let mut stream = stream.clone();
+                loop {
+                    match stream.next()? {
+                        Some((value, next)) if call(func, &value)?.get_bool() => {
+                            break Some((value, rest(Generator::Filter(next, func.clone()))));
+                        }
+                        Some((_, next)) => stream = next,
+                        None => break None,
+                    }
+                }
+            }
+            Generator::Take(_, 0) => None,
+            Generator::Take(stream, count) => stream
+                .next()?
+                .map(|(value, next)| (value, rest(Generator::Take(next, count - 1)))),
+            Generator::Drop(stream, count) => {
+                let mut stream = stream.clone();
+                for _ in 0..*count {
+                    match stream.next()? {
+                        Some((_, next)) => stream = next,
+                        None => return Ok(None),
+                    }
+                }
+                stream.next()?
+            }
+            Generator::TakeWhile(stream, func) => match stream.next()? {
+                Some((value, next)) if call(func, &value)?.get_bool() => {
+                    Some((value, rest(Generator::TakeWhile(next, func.clone()))))
+                }
+                _ => None,
+            },
+            Generator::Zip(streams) => {
+                let (mut values, mut nexts) = (vec![], vec![]);
+                for stream in streams {
+                    match stream.next()? {
+                        Some((value, next)) => {
+                            values.push(value);
+                            nexts.push(next);
+                        }
+                        None => return Ok(None),
+                    }
+                }
+                Some((Type::List(values), rest(Generator::Zip(nexts))))
+            }
+        })
+    }
+
+    /// All of the elements as the list, that never ends if the stream is endless
+    fn realize(&self) -> Result<Vec<Type>, LazoError> {
+        let runtime = self.scope.runtime();
+        let (mut list, mut stream) = (vec![], self.clone());
+        while let Some((value, next)) = stream.next()? {
+            runtime.check_len("list", list.len() + 1)?;
+            list.push(value);
+            stream = next;
+        }
+        Ok(list)
+    }
+
+    /// Whether the elements are the ones of the list, it's read only as long as the list
+    fn matches(&self, list: &[Type]) -> Result<bool, LazoError> {
+        let mut stream = self.clone();
+        for item in list {
+            match stream.next()? {
+                Some((value, next)) if value == *item => stream = next,
+                _ => return Ok(false),
+            }
+        }
+        Ok(stream.next()?.is_none())
+    }
+
+    /// Elements that're already made, the rest is shown as `...`
+    fn made(&self) -> Vec<String> {
+        let (mut made, mut node) = (vec![], self.node.clone());
+        loop {
+            let next = match &*node.borrow() {
+                Node::Cons(value, rest) => {
+                    made.push(format!("{value:?}"));
+                    rest.node.clone()
+                }
+                Node::Empty => break,
+                Node::Pending(_) | Node::Making => {
+                    made.push("...".to_string());
+                    break;
+                }
+            };
+            node = next;
+        }
+        made
+    }
+
+    /// Stream itself if it should be lazy, otherwise the list of its elements
+    fn lazy_if(self, lazy: bool) -> Result<Type, LazoError> {
+        match lazy {
+            true => Ok(Type::Stream(self)),
+            false => Ok(Type::List(self.realize()?)),
+        }
+    }
+
+    fn take_rest(node: &RefCell<Node>) -> Option<Rc<RefCell<Node>>> {
+        match node.replace(Node::Empty) {
+            Node::Cons(_, rest) => Some(rest.node.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Long chain of made elements is dropped in the loop, the recursion would overflow the stack
+impl Drop for Stream {
+    fn drop(&mut self) {
+        // Only the last owner of the node drops the rest
+        if Rc::strong_count(&self.node) > 1 {
+            return;
+        }
+        let mut next = Stream::take_rest(&self.node);
+        while let Some(node) = next {
+            next = match Rc::strong_count(&node) {
+                1 => Stream::take_rest(&node),
+                _ => None,
+            };
+        }
+    }
+}
+
+/// Type that's defined by `define-record`, with the names of its fields
+pub struct RecordType {
+    name: String,
+    fields: Vec<String>,
+}
+
+/// Value of the record type, that has the values of the fields in order
+pub struct Record {
+    kind: Rc<RecordType>,
+    values: Vec<Type>,
+}
+
+impl RecordType {
+    /// Constructor, predicate, accessors and updaters of the type like `make-point`,
+    /// `point?`, `point-x` and `point-with-x`, the constructor is the first one
+    fn functions(self: &Rc<RecordType>) -> Vec<(String, Type)> {
+        let name = &self.name;
+        let kind = self.clone();
+        let mut functions = vec![builtin(
+            &format!("make-{name}"),
+            Arity::Exact(self.fields.len()),
+            &format!("Make {name} of {}", self.fields.join(" ")),
+            move |params, scope| {
+                let mut values = vec![];
+                for i in &params {
+                    values.push(i.eval(scope)?);
+                }
+                Ok(Type::Record(Rc::new(Record {
+                    kind: kind.clone(),
+                    values,
+                })))
+            },
+        )];
+        let kind = self.clone();
+        functions.push(builtin(
+            &format!("{name}?"),
+            Arity::Exact(1),
+            &format!("Whether the value is {name}"),
+            move |params, scope| {
+                Ok(Type::Bool(matches!(
+                    params[0].eval(scope)?,
+                    Type::Record(record) if Rc::ptr_eq(&record.kind, &kind)
+                )))
+            },
+        ));
+        for (index, field) in self.fields.iter().enumerate() {
+            let kind = self.clone();
+            functions.push(builtin(
+                &format!("{name}-{field}"),
+                Arity::Exact(1),
+                &format!("Field `{field}` of the {name}"),
+                move |params, scope| {
+                    let record = kind.expect(params[0].eval(scope)?)?;
+                    Ok(record.values[index].clone())
+                },
+            ));
+            let kind = self.clone();
+            functions.push(builtin(
+                &format!("{name}-with-{field}"),
+                Arity::Exact(2),
+                &format!("Copy of the {name} whose `{field}` is the value"),
+                move |params, scope| {
+                    let mut values = kind.expect(params[0].eval(scope)?)?.values.clone();
+                    values[index] = params[1].eval(scope)?;
+                    Ok(Type::Record(Rc::new(Record {
+                        kind: kind.clone(),
+                        values,
+                    })))
+                },
+            ));
+        }
+        functions
+    }
+
+    fn expect(self: &Rc<RecordType>, value: Type) -> Result<Rc<Record>, LazoError> {
+        match value {
+            Type::Record(record) if Rc::ptr_eq(&record.kind, self) => Ok(record),
+            other => Err(other.mismatch(&self.name)),
+        }
+    }
+}
+
+impl From<bool> for Type {
+    fn from(value: bool) -> Type {
+        Type::Bool(value)
+    }
+}
+
+impl From<i64> for Type {
+    fn from(value: i64) -> Type {
+        Type::Number(Number::Integer(BigInt::from(value)))
+    }
+}
+
+impl From<BigInt> for Type {
+    fn from(value: BigInt) -> Type {
+        Type::Number(Number::Integer(value))
+    }
+}
+
+impl From<f64> for Type {
+    fn from(value: f64) -> Type {
+        Type::Number(Number::Float(value))
+    }
+}
+
+impl From<&str> for Type {
+    fn from(value: &str) -> Type {
+        Type::String(value.to_string())
+    }
+}
+
+impl From<String> for Type {
+    fn from(value: String) -> Type {
+        Type::String(value)
+    }
+}
+
+impl From<()> for Type {
+    fn from(_: ()) -> Type {
+        Type::Null
+    }
+}
+
+impl<T: Into<Type>> From<Option<T>> for Type {
+    fn from(value: Option<T>) -> Type {
+        value.map(Into::into).unwrap_or(Type::Null)
+    }
+}
+
+impl<T: Into<Type>> From<Vec<T>> for Type {
+    fn from(value: Vec<T>) -> Type {
+        Type::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Type>> From<HashMap<String, T>> for Type {
+    fn from(value: HashMap<String, T>) -> Type {
+        Type::Map(
+            value
+                .into_iter()
+                .map(|(k, v)| {
+                    let k = Type::String(k);
+                    (k.key(), (k, v.into()))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Type {
+    /// Value that's not suspended, to convert it to Rust's one
+    fn forced(self) -> Result<Type, LazoError> {
+        match self {
+            Type::Thunk(thunk) => thunk.force()?.forced(),
+            value => Ok(value),
+        }
+    }
+}
+
+impl TryFrom<Type> for bool {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<bool, LazoError> {
+        match value.forced()? {
+            Type::Bool(b) => Ok(b),
+            other => Err(other.mismatch("bool")),
+        }
+    }
+}
+
+impl TryFrom<Type> for i64 {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<i64, LazoError> {
+        match value.forced()? {
+            Type::Number(Number::Integer(i)) => i.to_i64().ok_or(LazoError::Type(format!(
+                "integer `{i}` is too large to be converted"
+            ))),
+            other => Err(other.mismatch("integer")),
+        }
+    }
+}
+
+impl TryFrom<Type> for BigInt {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<BigInt, LazoError> {
+        match value.forced()? {
+            Type::Number(Number::Integer(i)) => Ok(i),
+            other => Err(other.mismatch("integer")),
+        }
+    }
+}
+
+impl TryFrom<Type> for f64 {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<f64, LazoError> {
+        match value.forced()? {
+            Type::Number(n) => Ok(n.to_f64()),
+            other => Err(other.mismatch("number")),
+        }
+    }
+}
+
+impl TryFrom<Type> for String {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<String, LazoError> {
+        match value.forced()? {
+            Type::String(s) => Ok(s),
+            other => Err(other.mismatch("string")),
+        }
+    }
+}
+
+impl<T: TryFrom<Type, Error = LazoError>> TryFrom<Type> for Vec<T> {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<Vec<T>, LazoError> {
+        match value.forced()? {
+            Type::List(list) => list.into_iter().map(T::try_from).collect(),
+            Type::Stream(stream) => stream.realize()?.into_iter().map(T::try_from).collect(),
+            other => Err(other.mismatch("list")),
+        }
+    }
+}
+
+impl<T: TryFrom<Type, Error = LazoError>> TryFrom<Type> for Option<T> {
+    type Error = LazoError;
+    fn try_from(value: Type) -> Result<Option<T>, LazoError> {
+        match value.forced()? {
+            Type::Null => Ok(None),
+            other => T::try_from(other).map(Some),
+        }
+    }
+}
+
+/// Number of the numeric tower, exact ones become float only when they're mixed with it
+#[derive(Clone)]
+pub enum Number {
+    /// Integer of arbitrary precision
+    Integer(BigInt),
+    /// Exact fraction, that's never an integer
+    Rational(BigRational),
+    Float(f64),
+}
+
+impl Number {
+    fn parse(text: &str) -> Option<Number> {
+        if let Ok(i) = text.parse::<BigInt>() {
+            return Some(Number::Integer(i));
+        }
+        // Rational literal like `1/3`
+        if let Some((numer, denom)) = text.split_once('/') {
+            if let (Ok(numer), Ok(denom)) = (numer.parse::<BigInt>(), denom.parse::<BigInt>()) {
+                if !denom.is_zero() {
+                    return Some(Number::from_ratio(BigRational::new(numer, denom)));
+                }
+            }
+        }
+        text.parse::<f64>().ok().map(Number::Float)
+    }
+
+    fn from_ratio(ratio: BigRational) -> Number {
+        if ratio.is_integer() {
+            Number::Integer(ratio.to_integer())
+        } else {
+            Number::Rational(ratio)
+        }
+    }
+
+    /// Exact value of the number, float is converted as it's represented in binary
+    fn to_ratio(&self) -> Option<BigRational> {
+        match self {
+            Number::Integer(i) => Some(BigRational::from_integer(i.clone())),
+            Number::Rational(r) => Some(r.clone()),
+            Number::Float(f) => BigRational::from_float(*f),
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        match self {
+            Number::Integer(i) => i.to_f64().unwrap_or(f64::NAN),
+            Number::Rational(r) => r.to_f64().unwrap_or(f64::NAN),
+            Number::Float(f) => *f,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        match self {
+            Number::Integer(i) => i.is_zero(),
+            Number::Rational(_) => false,
+            Number::Float(f) => *f == 0.0,
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        !matches!(self, Number::Float(_))
+    }
+
+    /// Exact form of the number that's the key of the map, `nan` and infinities are as they're
+    fn key(&self) -> String {
+        match self.to_ratio() {
+            Some(ratio) if ratio.is_integer() => ratio.to_integer().to_string(),
+            Some(ratio) => ratio.to_string(),
+            None => self.to_string(),
+        }
+    }
+
+    /// Bits of the exact number, the float has the fixed size
+    fn bits(&self) -> usize {
+        let bits = match self {
+            Number::Integer(i) => i.bits(),
+            Number::Rational(r) => r.numer().bits() + r.denom().bits(),
+            Number::Float(_) => 0,
+        };
+        bits.try_into().unwrap_or(usize::MAX)
+    }
+
+    /// Bits that the power is estimated to have, to know it's too large before computing it
+    fn power_bits(&self, exponent: &Number) -> usize {
+        match exponent {
+            Number::Integer(exponent) => {
+                let exponent = exponent.abs().to_f64().unwrap_or(f64::INFINITY);
+                (self.bits().saturating_sub(1) as f64 * exponent) as usize
+            }
+            Number::Rational(_) | Number::Float(_) => 0,
+        }
+    }
+
+    /// Apply the operation in the wider type of both numbers
+    fn promote(
+        &self,
+        other: &Number,
+        integer: fn(&BigInt, &BigInt) -> BigInt,
+        ratio: fn(&BigRational, &BigRational) -> BigRational,
+        float: fn(f64, f64) -> f64,
+    ) -> Number {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Number::Integer(integer(a, b)),
+            (Number::Float(_), _) | (_, Number::Float(_)) => {
+                Number::Float(float(self.to_f64(), other.to_f64()))
+            }
+            _ => match (self.to_ratio(), other.to_ratio()) {
+                (Some(a), Some(b)) => Number::from_ratio(ratio(&a, &b)),
+                _ => Number::Float(float(self.to_f64(), other.to_f64())),
+            },
+        }
+    }
+
+    /// Exact division by zero is an error, while float one is infinity or NaN
+    fn check_divisor(&self, divisor: &Number) -> Result<(), LazoError> {
+        if self.is_exact() && divisor.is_exact() && divisor.is_zero() {
+            Err(LazoError::Runtime("division by zero".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn divide(&self, divisor: &Number) -> Result<Number, LazoError> {
+        self.check_divisor(divisor)?;
+        Ok(match (self, divisor) {
+            // Integer division keeps the fraction
+            (Number::Integer(a), Number::Integer(b)) => {
+                Number::from_ratio(BigRational::new(a.clone(), b.clone()))
+            }
+            _ => self.promote(divisor, |a, b| a / b, |a, b| a / b, |a, b| a / b),
+        })
+    }
+
+    /// Remainder that has the same sign as the dividend
+    fn remainder(&self, divisor: &Number) -> Result<Number, LazoError> {
+        self.check_divisor(divisor)?;
+        Ok(self.promote(divisor, |a, b| a % b, |a, b| a % b, |a, b| a % b))
+    }
+
+    fn power(&self, exponent: &Number) -> Result<Number, LazoError> {
+        match (self, exponent) {
+            (Number::Integer(base), Number::Integer(exponent)) if !exponent.is_negative() => {
+                let exponent = exponent.to_u32().ok_or(LazoError::Runtime(
+                    "exponent is too large to compute exactly".to_string(),
+                ))?;
+                Ok(Number::Integer(base.pow(exponent)))
+            }
+            (Number::Integer(_) | Number::Rational(_), Number::Integer(exponent)) => {
+                let exponent = exponent.to_i32().ok_or(LazoError::Runtime(
+                    "exponent is too large to compute exactly".to_string(),
+                ))?;
+                if self.is_zero() && exponent < 0 {
+                    return Err(LazoError::Runtime("division by zero".to_string()));
+                }
+                let base = self.to_ratio().unwrap_or_default();
+                Ok(Number::from_ratio(Pow::pow(&base, exponent)))
+            }
+            _ => Ok(Number::Float(self.to_f64().powf(exponent.to_f64()))),
+        }
+    }
+
+    /// Part of the fraction, float is converted to exact one and back
+    fn fraction(&self, part: fn(&BigRational) -> BigInt) -> Result<Number, LazoError> {
+        let ratio = self.to_ratio().ok_or(LazoError::Runtime(format!(
+            "`{self}` doesn't have the fraction"
+        )))?;
+        let result = Number::Integer(part(&ratio));
+        Ok(if self.is_exact() {
+            result
+        } else {
+            Number::Float(result.to_f64())
+        })
+    }
+}
+
+impl Add for &Number {
+    type Output = Number;
+    fn add(self, other: &Number) -> Number {
+        self.promote(other, |a, b| a + b, |a, b| a + b, |a, b| a + b)
+    }
+}
+
+impl Sub for &Number {
+    type Output = Number;
+    fn sub(self, other: &Number) -> Number {
+        self.promote(other, |a, b| a - b, |a, b| a - b, |a, b| a - b)
+    }
+}
+
+impl Mul for &Number {
+    type Output = Number;
+    fn mul(self, other: &Number) -> Number {
+        self.promote(other, |a, b| a * b, |a, b| a * b, |a, b| a * b)
+    }
+}
+
+impl Neg for &Number {
+    type Output = Number;
+    fn neg(self) -> Number {
+        match self {
+            Number::Integer(i) => Number::Integer(-i),
+            Number::Rational(r) => Number::Rational(-r),
+            Number::Float(f) => Number::Float(-f),
+        }
+    }
+}
+
+/// Numbers are compared by their values regardless of exactness, like `(= 1 1.0)`
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => a.partial_cmp(b),
+            (Number::Float(_), _) | (_, Number::Float(_)) => {
+                self.to_f64().partial_cmp(&other.to_f64())
+            }
+            _ => self.to_ratio().partial_cmp(&other.to_ratio()),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Integer(i) => write!(f, "{i}"),
+            Number::Rational(r) => write!(f, "{r}"),
+            // Float is always written with the point, like `1.0`
+            Number::Float(n) => write!(f, "{n:?}"),
+        }
+    }
+}
+
+impl Type {
+    fn get_number(&self) -> Number {
+        match &self {
+            Type::Number(n) => n.to_owned(),
+            Type::String(s) | Type::Symbol(s) => {
+                Number::parse(s.trim()).unwrap_or(Number::Integer(BigInt::zero()))
+            }
+            Type::Bool(b) => Number::Integer(BigInt::from(*b as u8)),
+            Type::Expr(x, _) | Type::List(x) => x.first().unwrap_or(&Type::Null).get_number(),
+            Type::Stream(stream) => match stream.next() {
+                Ok(Some((first, _))) => first.get_number(),
+                _ => Number::Integer(BigInt::zero()),
+            },
+            Type::Thunk(t) => t.value().unwrap_or(Type::Null).get_number(),
+            Type::Map(_)
+            | Type::Record(_)
+            | Type::Function(_)
+            | Type::Error(_)
+            | Type::Tail(..)
+            | Type::Null => Number::Integer(BigInt::zero()),
+        }
+    }
+
+    /// Error of strict mode that the value isn't the expected type
+    fn mismatch(&self, expected: &str) -> LazoError {
+        LazoError::Type(format!(
+            "expected {expected}, got {} `{self:?}`",
+            self.get_type()
+        ))
+    }
+
+    /// Number of the value, that's not converted in strict mode
+    fn expect_number(&self, scope: &Scope) -> Result<Number, LazoError> {
+        match self {
+            Type::Number(n) => Ok(n.clone()),
+            Type::Thunk(t) if t.value().is_some() => {
+                t.value().unwrap_or(Type::Null).expect_number(scope)
+            }
+            _ if scope.is_strict() => Err(self.mismatch("number")),
+            _ => Ok(self.get_number()),
+        }
+    }
+
+    fn expect_bool(&self, scope: &Scope) -> Result<bool, LazoError> {
+        match self {
+            Type::Bool(b) => Ok(*b),
+            Type::Thunk(t) if t.value().is_some() => {
+                t.value().unwrap_or(Type::Null).expect_bool(scope)
+            }
+            _ if scope.is_strict() => Err(self.mismatch("bool")),
+            _ => Ok(self.get_bool()),
+        }
+    }
+
+    /// Elements of the list, quoted code is also a list in strict mode
+    fn expect_list(&self, scope: &Scope) -> Result<Vec<Type>, LazoError> {
+        match self {
+            Type::List(l) | Type::Expr(l, _) => Ok(l.clone()),
+            Type::Stream(stream) => stream.realize(),
+            Type::Thunk(t) if t.value().is_some() => {
+                t.value().unwrap_or(Type::Null).expect_list(scope)
+            }
+            _ if scope.is_strict() => Err(self.mismatch("list")),
+            _ => Ok(self.get_list()),
+        }
+    }
+
+    /// Stream of the value if it's the lazy sequence
+    fn stream(&self) -> Option<Stream> {
+        match self {
+            Type::Stream(stream) => Some(stream.clone()),
+            Type::Thunk(t) => t.value().and_then(|value| value.stream()),
+            _ => None,
+        }
+    }
+
+    /// Stream of the elements, the list is also read one by one
+    fn expect_stream(&self, scope: &Scope) -> Result<Stream, LazoError> {
+        match self.stream() {
+            Some(stream) => Ok(stream),
+            None => Ok(Stream::items(self.expect_list(scope)?, scope)),
+        }
+    }
+
+    /// Value that the stream is realized as the list, to print or compare it
+    /// Equality of `=`, streams are compared by identity as they may be endless,
+    /// and the stream is read only as long as the list that it's compared with
+    fn equals(&self, other: &Type) -> Result<bool, LazoError> {
+        let list = |i: &Type| match i {
+            Type::Thunk(t) => t.value(),
+            other => Some(other.clone()),
+        };
+        match (self.stream(), other.stream()) {
+            (Some(a), Some(b)) => Ok(Rc::ptr_eq(&a.node, &b.node)),
+            (Some(stream), None) => match list(other) {
+                Some(Type::List(items)) => stream.matches(&items),
+                _ => Ok(false),
+            },
+            (None, Some(stream)) => match list(self) {
+                Some(Type::List(items)) => stream.matches(&items),
+                _ => Ok(false),
+            },
+            (None, None) => Ok(self == other),
+        }
+    }
+
+    /// Whether all of the values are equal by `=`
+    fn all_equal(values: &[Type]) -> Result<bool, LazoError> {
+        for window in values.windows(2) {
+            if !window[0].equals(&window[1])? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn realized(self) -> Result<Type, LazoError> {
+        match self.stream() {
+            Some(stream) => Ok(Type::List(stream.realize()?)),
+            None => Ok(self),
+        }
+    }
+
+    fn get_string(&self) -> String {
+        match &self {
+            Type::Number(n) => n.to_string(),
+            Type::String(s) => s.to_owned(),
+            Type::Bool(b) => b.to_string(),
+            Type::Symbol(v) => v.to_owned(),
+            Type::Error(err) => err.message(),
+            Type::Thunk(t) if t.value().is_some() => t.value().unwrap_or(Type::Null).get_string(),
+            other => format!("{other:?}"),
+        }
+    }
+
+    fn get_bool(&self) -> bool {
+        match &self {
+            Type::Number(n) => !n.is_zero(),
+            Type::String(s) | Type::Symbol(s) => !s.is_empty(),
+            Type::Expr(s, _) | Type::List(s) => !s.is_empty(),
+            Type::Map(m) => !m.is_empty(),
+            Type::Stream(stream) => matches!(stream.next(), Ok(Some(_))),
+            Type::Record(_) => true,
+            Type::Bool(b) => *b,
+            Type::Thunk(t) => t.value().unwrap_or(Type::Null).get_bool(),
+            Type::Function(_) | Type::Error(_) | Type::Tail(..) | Type::Null => false,
+        }
+    }
+
+    pub fn get_type(&self) -> String {
+        match &self {
+            Type::Number(_) => "number".to_string(),
+            Type::String(_) => "string".to_string(),
+            Type::Bool(_) => "bool".to_string(),
+            Type::Expr(..) => "expr".to_string(),
+            Type::Symbol(_) => "symbol".to_string(),
+            Type::List(_) => "list".to_string(),
+            Type::Stream(_) => "stream".to_string(),
+            Type::Record(record) => record.kind.name.clone(),
+            Type::Map(_) => "map".to_string(),
+            Type::Null => "null".to_string(),
+            Type::Function(_) => "function".to_string(),
+            Type::Thunk(_) => "thunk".to_string(),
+            Type::Error(_) => "error".to_string(),
+            Type::Tail(..) => "tail".to_string(),
+        }
+    }
+
+    /// Identity that `eq?` checks, lists and maps are copied when they're passed
+    /// so they aren't identical to anything except that both are empty
+    fn is(&self, other: &Type) -> bool {
+        match (self, other) {
+            // Exact and inexact numbers aren't the same value, even if they're equal by `=`
+            (Type::Number(a), Type::Number(b)) => a.is_exact() == b.is_exact() && a == b,
+            (Type::List(a), Type::List(b)) | (Type::Expr(a, _), Type::Expr(b, _)) => {
+                a.is_empty() && b.is_empty()
+            }
+            (Type::Map(a), Type::Map(b)) => a.is_empty() && b.is_empty(),
+            (Type::Thunk(a), Type::Thunk(b)) => Rc::ptr_eq(&a.0, &b.0),
+            (Type::Stream(a), Type::Stream(b)) => Rc::ptr_eq(&a.node, &b.node),
+            (Type::Record(a), Type::Record(b)) => Rc::ptr_eq(a, b),
+            _ => self == other,
+        }
+    }
+
+    /// Key of the map, values that're equal by `=` are the same key,
+    /// numbers are written in the exact form so that `1` and `1.0` are the same one
+    fn key(&self) -> String {
+        let keys = |items: &[Type]| items.iter().map(Type::key).collect::<Vec<String>>();
+        match self {
+            Type::Number(n) => n.key(),
+            Type::List(items) => format!("[{}]", keys(items).join(" ")),
+            Type::Expr(items, _) => format!("({})", keys(items).join(" ")),
+            Type::Map(m) => format!(
+                "{{{}}}",
+                m.values()
+                    .map(|(k, v)| format!("{} {}", k.key(), v.key()))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            // Types of the same name from other definitions aren't the same type
+            Type::Record(record) => format!(
+                "#{}<{:p}>{{{}}}",
+                record.kind.name,
+                Rc::as_ptr(&record.kind),
+                keys(&record.values).join(" ")
+            ),
+            Type::Thunk(t) if t.value().is_some() => t.value().unwrap_or(Type::Null).key(),
+            // Values that're compared by identity are keyed by the address
+            Type::Thunk(t) => format!("#<thunk {:p}>", Rc::as_ptr(&t.0)),
+            Type::Stream(stream) => format!("#<stream {:p}>", Rc::as_ptr(&stream.node)),
+            Type::Error(err) => format!("#<error {:p}>", Rc::as_ptr(err)),
+            Type::Function(Function::BuiltIn(func)) => format!("#<builtin {:p}>", Rc::as_ptr(func)),
+            Type::Function(Function::UserDefined(func)) => {
+                format!("#<function {:p}>", Rc::as_ptr(func))
+            }
+            Type::Function(Function::Macro(func)) => format!("#<macro {:p}>", Rc::as_ptr(func)),
+            other => format!("{other:?}"),
+        }
+    }
+
+    fn get_map(&self) -> BTreeMap<String, (Type, Type)> {
+        match self {
+            Type::Map(m) => m.to_owned(),
+            Type::Thunk(t) if t.value().is_some() => t.value().unwrap_or(Type::Null).get_map(),
+            other => other
+                .get_list()
+                .iter()
+                .filter_map(|pair| match pair.get_list().as_slice() {
+                    [k, v] => Some((k.key(), (k.to_owned(), v.to_owned()))),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Code that evaluates to the value, to pass it as the argument
+    fn quote(&self) -> Type {
+        match self {
+            Type::Symbol(_) | Type::Expr(..) => {
+                Type::Expr(vec![Type::Symbol("quote".to_string()), self.clone()], None)
+            }
+            value => value.clone(),
+        }
+    }
+
+    /// Whether the value matches the type annotation
+    fn has_type(&self, annotation: &str) -> bool {
+        compatible(annotation, &self.get_type())
+    }
+
+    fn span(&self) -> Option<Span> {
+        match self {
+            Type::Expr(_, span) => span.clone(),
+            _ => None,
+        }
+    }
+
+    fn get_list(&self) -> Vec<Type> {
+        match &self {
+            Type::Expr(e, _) => e.to_owned(),
+            Type::List(l) => l.to_owned(),
+            Type::Stream(stream) => stream.realize().unwrap_or_default(),
+            // Map is the list of the pairs
+            Type::Map(m) => m
+                .values()
+                .map(|(k, v)| Type::List(vec![k.to_owned(), v.to_owned()]))
+                .collect(),
+            Type::Thunk(t) if t.value().is_some() => t.value().unwrap_or(Type::Null).get_list(),
+            other => vec![other.to_owned().to_owned()],
+        }
+    }
+}
+
+/// Structural equality, values of different types are never equal like `1` and `"1"`
+impl PartialEq for Type {
+    fn eq(&self, other: &Type) -> bool {
+        match (self, other) {
+            // Numbers follow IEEE 754, so `nan` isn't equal to itself
+            (Type::Number(a), Type::Number(b)) => a == b,
+            (Type::String(a), Type::String(b)) | (Type::Symbol(a), Type::Symbol(b)) => a == b,
+            (Type::Bool(a), Type::Bool(b)) => a == b,
+            (Type::Null, Type::Null) => true,
+            (Type::List(a), Type::List(b)) | (Type::Expr(a, _), Type::Expr(b, _)) => a == b,
+            (Type::Map(a), Type::Map(b)) => a == b,
+            // Streams may be endless, so they're compared by identity
+            (Type::Stream(a), Type::Stream(b)) => Rc::ptr_eq(&a.node, &b.node),
+            // Records of the same type are equal if all of the fields are
+            (Type::Record(a), Type::Record(b)) => {
+                Rc::ptr_eq(&a.kind, &b.kind) && a.values == b.values
+            }
+            (Type::Function(a), Type::Function(b)) => a.is(b),
+            (Type::Error(a), Type::Error(b)) => Rc::ptr_eq(a, b),
+            (Type::Thunk(a), Type::Thunk(b)) => {
+                Rc::ptr_eq(&a.0, &b.0)
+                    || matches!((a.value(), b.value()), (Some(a), Some(b)) if a == b)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Debug for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fmt = match &self {
+            Type::String(s) => format!("{s:?}"),
+            Type::Number(n) => n.to_string(),
+            Type::Bool(b) => b.to_string(),
+            Type::Function(Function::UserDefined(func)) => {
+                format!(
+                    "(lambda ({}) {}{})",
+                    func.args
+                        .iter()
+                        .map(|(i, annotation)| match annotation {
+                            Some(annotation) => format!("({i:?} : {annotation})"),
+                            None => format!("{i:?}"),
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" "),
+                    match &func.ret {
+                        Some(ret) => format!("-> {ret} "),
+                        None => String::new(),
+                    },
+                    func.body
+                        .iter()
+                        .map(|i| format!("{i:?}"))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                )
+            }
+            Type::Function(Function::BuiltIn(func)) => format!("function({})", func.name),
+            Type::Function(Function::Macro(mac)) => format!(
+                "(syntax-rules ({}) {})",
+                mac.literals.join(" "),
+                mac.rules
+                    .iter()
+                    .map(|(pattern, template)| format!("({pattern:?} {template:?})"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            Type::Symbol(v) => v.to_owned(),
+            Type::List(l) => format!(
+                "[{}]",
+                l.iter()
+                    .map(|x| format!("{x:?}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            Type::Stream(stream) => format!("[{}]", stream.made().join(" ")),
+            Type::Record(record) => format!(
+                "#{}{{{}}}",
+                record.kind.name,
+                record
+                    .kind
+                    .fields
+                    .iter()
+                    .zip(&record.values)
+                    .map(|(field, value)| format!("{field}: {value:?}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            Type::Map(m) => format!(
+                "{{{}}}",
+                m.values()
+                    .map(|(k, v)| format!("{k:?} {v:?}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            Type::Expr(l, _) => format!(
+                "({})",
+                l.iter()
+                    .map(|x| format!("{x:?}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            Type::Thunk(t) => match &*t.0.borrow() {
+                Suspension::Delayed(expr, _) => format!("(delay {expr:?})"),
+                Suspension::Compiled(code, _) => format!("(delay {:?})", code.source),
+                Suspension::Forcing => "(delay ...)".to_string(),
+                Suspension::Forced(value) => format!("{value:?}"),
+            },
+            Type::Error(err) => format!("(error '{} {:?})", err.kind(), err.payload()),
+            Type::Tail(expr, _) => format!("{expr:?}"),
+            Type::Null => "null".to_string(),
+        };
+        write!(f, "{fmt}")
+    }
+}
+
+impl Type {
+    fn eval(&self, scope: &mut Scope) -> Result<Type, LazoError> {
+        let depth = scope.runtime().call_stack.borrow().len();
+        self.eval_in(scope, depth)
+    }
+
+    /// Evaluate the code as the function call at the depth of the call stack
+    fn eval_in(&self, scope: &mut Scope, depth: usize) -> Result<Type, LazoError> {
+        let runtime = scope.runtime();
+
+        // Tail calls are evaluated in this loop instead of recursion to run in constant stack
+        let mut expr = Cow::Borrowed(self);
+        let mut scope = scope.clone();
+        // Expression that the code in tail position comes from, variables have no location
+        let mut located = None;
+        let result = loop {
+            let Type::Expr(list, span) = &*expr else {
+                break match &*expr {
+                    // Loading variable from scope
+                    Type::Symbol(name) => match scope.get(name) {
+                        // Forcing suspended argument
+                        Some(Type::Thunk(thunk)) => thunk.force(),
+                        Some(value) => Ok(value),
+                        None => Err(scope.unbound(name)),
+                    },
+                    value => Ok(value.clone()),
+                }
+                .map_err(|err| err.at(located));
+            };
+            let result = runtime.tick();
+            match result.and_then(|_| Type::call(list, span, &mut scope, depth)) {
+                Ok(Type::Tail(next, next_scope)) => {
+                    located = span.clone();
+                    expr = Cow::Owned(*next);
+                    scope = next_scope;
+                }
+                Ok(value) => break Ok(value),
+                Err(err) => break Err(err.at(span.clone()).at(located)),
+            }
+        };
+
+        settle(result, depth, &runtime)
+    }
+
+    /// Call the function, the code in tail position is returned as `Type::Tail`
+    fn call(
+        expr: &[Type],
+        span: &Option<Span>,
+        scope: &mut Scope,
+        depth: usize,
+    ) -> Result<Type, LazoError> {
+        let func = if let Some(func) = expr.first() {
+            func.eval(scope)?
+        } else {
+            return Err(LazoError::Syntax(
+                "empty expression can't be evaluated".to_string(),
+            ));
+        };
+        Type::apply(func, expr, span, scope, depth)
+    }
+
+    /// Call the function that's evaluated from the head of the expression
+    fn apply(
+        func: Type,
+        expr: &[Type],
+        span: &Option<Span>,
+        scope: &mut Scope,
+        depth: usize,
+    ) -> Result<Type, LazoError> {
+        if let Type::Function(Function::BuiltIn(func)) = func {
+            if !func.arity.accepts(expr[1..].len()) {
+                return Err(LazoError::Arity(
+                    func.name.clone(),
+                    expr[1..].len(),
+                    func.arity,
+                ));
+            }
+            let value = (func.func)(expr[1..].to_vec(), scope)?;
+            scope.runtime().check_size(&value)?;
+            Ok(value)
+        } else if let Type::Function(Function::UserDefined(func)) = func {
+            // Check arguments length
+            if func.args.len() != expr[1..].len() {
+                return Err(LazoError::Arity(
+                    func.name(),
+                    expr[1..].len(),
+                    Arity::Exact(func.args.len()),
+                ));
+            }
+
+            // Setting argument by suspended value, that's evaluated when it's needed,
+            // and argument from untyped code is checked when it's evaluated
+            let args = func.args.iter().zip(expr[1..].iter());
+            let args: Vec<(String, Type)> = args
+                .map(|((k, annotation), v)| {
+                    let v = checked(annotation, v, span).unwrap_or(v.clone());
+                    (k.get_string(), v.suspend(scope))
+                })
+                .collect();
+            if scope.runtime().engine.get() == Engine::Vm {
+                return func.enter(args.into_iter().map(|(_, v)| v).collect(), span, depth);
+            }
+
+            // Setting arguemnt and its value on top of the captured environment,
+            // the argument of the list parameter is destructured right now
+            let mut func_scope = Scope::new(&func.env);
+            enter_frame(&scope.runtime(), depth, func.name(), span)?;
+            for ((param, _), (k, v)) in func.args.iter().zip(args) {
+                match param {
+                    Type::List(_) => bind_pattern(param, v, &func_scope)?,
+                    _ => func_scope.insert(k, v),
+                }
+            }
+
+            // Execution of function's code
+            match &func.ret {
+                // Returned value is needed to check it, so it's not a tail call
+                Some(ret) => {
+                    let value = Type::eval_block(&func.body, &mut func_scope)?.resolve()?;
+                    func.check(ret, value)
+                }
+                None => Type::eval_block(&func.body, &mut func_scope),
+            }
+        } else if let Type::Function(Function::Macro(mac)) = func {
+            // Expanded code is evaluated instead of the macro call
+            let shadowed = |name: &str, value: &Type| scope.get(name).as_ref() != Some(value);
+            Ok(mac.expand(expr, shadowed)?.tail(scope))
+        } else {
+            Err(LazoError::Syntax(format!(
+                "first atom in expression should be function, but provided `{:?}` is not function",
+                expr.first().cloned().unwrap_or(Type::Null)
+            )))
+        }
+    }
+
+    /// Evaluate the lines in order, the last one is evaluated in tail position
+    fn eval_block(block: &[Type], scope: &mut Scope) -> Result<Type, LazoError> {
+        if let Some((last, block)) = block.split_last() {
+            for line in block {
+                line.eval(scope)?;
+            }
+            Ok(last.tail(scope))
+        } else {
+            Ok(Type::Null)
+        }
+    }
+
+    /// Evaluate the code in tail position right now, to use its value
+    fn resolve(self) -> Result<Type, LazoError> {
+        match self {
+            Type::Tail(expr, mut scope) => expr.eval(&mut scope),
+            value => Ok(value),
+        }
+    }
+
+    /// Evaluate it later in the loop of `Type::eval` that called the builtin
+    fn tail(&self, scope: &Scope) -> Type {
+        Type::Tail(Box::new(self.clone()), scope.clone())
+    }
+
+    fn suspend(&self, scope: &Scope) -> Type {
+        match self {
+            Type::Number(_) | Type::String(_) | Type::Bool(_) | Type::Null => self.clone(),
+            // Cheap computation is done right now, otherwise thunks get chained
+            // by the accumulator of the loop and forcing it overflows the stack
+            _ if self.is_cheap(scope) => match self.eval(&mut scope.clone()) {
+                Ok(value) if !matches!(value, Type::Thunk(_)) => value,
+                _ => Type::Thunk(Thunk::new(self.clone(), scope.clone())),
+            },
+            _ => Type::Thunk(Thunk::new(self.clone(), scope.clone())),
+        }
+    }
+
+    /// Whether it's pure builtin call on values that're already evaluated, and that aren't streams
+    fn is_cheap(&self, scope: &Scope) -> bool {
+        match self {
+            Type::Number(_) | Type::String(_) | Type::Bool(_) | Type::Null => true,
+            Type::Symbol(name) => match scope.get(name) {
+                // Elements of the stream are made by calling the functions
+                Some(Type::Thunk(thunk)) => !matches!(thunk.value(), None | Some(Type::Stream(_))),
+                Some(Type::Stream(_)) | None => false,
+                Some(_) => true,
+            },
+            Type::Expr(list, _) => match list.split_first() {
+                Some((Type::Symbol(name), args)) => {
+                    CHEAP_BUILTINS.contains(&name.as_str())
+                        && matches!(scope.get(name), Some(Type::Function(Function::BuiltIn(_))))
+                        && args.iter().all(|arg| arg.is_cheap(scope))
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Number of the elements in the nested values, it stops counting when it's over the limit
+    fn count(&self, limit: usize) -> usize {
+        let mut count = 0;
+        let mut pending = vec![self];
+        while let Some(value) = pending.pop() {
+            let rest = pending.len();
+            match value {
+                Type::List(items) | Type::Expr(items, _) => pending.extend(items),
+                Type::Record(record) => pending.extend(&record.values),
+                Type::Map(map) => pending.extend(map.values().flat_map(|(k, v)| [k, v])),
+                _ => {}
+            }
+            count += pending.len() - rest;
+            if count > limit {
+                break;
+            }
+        }
+        count
+    }
+
+    /// Thunk of the variable that the code surely forces, that isn't evaluated yet
+    fn needed(&self, scope: &Scope) -> Option<Thunk> {
+        match self {
+            Type::Symbol(name) => match scope.get(name) {
+                Some(Type::Thunk(thunk))
+                    if matches!(
+                        *thunk.0.borrow(),
+                        Suspension::Delayed(..) | Suspension::Compiled(..)
+                    ) =>
+                {
+                    Some(thunk)
+                }
+                _ => None,
+            },
+            Type::Expr(list, _) => match list.split_first() {
+                Some((Type::Symbol(name), args))
+                    if STRICT_BUILTINS.contains(&name.as_str())
+                        && matches!(
+                            scope.get(name),
+                            Some(Type::Function(Function::BuiltIn(_)))
+                        ) =>
+                {
+                    args.iter().find_map(|arg| arg.needed(scope))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Build the code from the template, evaluating unquoted parts in it
+    fn quasiquote(&self, depth: usize, scope: &mut Scope) -> Result<Type, LazoError> {
+        Ok(match self {
+            Type::Expr(list, span) => match list.as_slice() {
+                [Type::Symbol(quote), expr] if quote == "unquote" => {
+                    if depth == 0 {
+                        expr.eval(scope)?
+                    } else {
+                        Type::Expr(
+                            vec![list[0].clone(), expr.quasiquote(depth - 1, scope)?],
+                            span.clone(),
+                        )
+                    }
+                }
+                [Type::Symbol(quote), expr] if quote == "quasiquote" => Type::Expr(
+                    vec![list[0].clone(), expr.quasiquote(depth + 1, scope)?],
+                    span.clone(),
+                ),
+                _ => Type::Expr(Type::quasiquote_seq(list, depth, scope)?, span.clone()),
+            },
+            Type::List(list) => Type::List(Type::quasiquote_seq(list, depth, scope)?),
+            other => other.clone(),
+        })
+    }
+
+    fn quasiquote_seq(
+        list: &[Type],
+        depth: usize,
+        scope: &mut Scope,
+    ) -> Result<Vec<Type>, LazoError> {
+        let mut result = vec![];
+        for i in list {
+            match i {
+                Type::Expr(splicing, _)
+                    if depth == 0
+                        && splicing.len() == 2
+                        && matches!(&splicing[0], Type::Symbol(quote) if quote == "unquote-splicing") =>
+                {
+                    result.extend(splicing[1].eval(scope)?.get_list())
+                }
+                other => result.push(other.quasiquote(depth, scope)?),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Variable that the compiler resolved, it's found by the slot without searching names
+#[derive(Clone)]
+enum Var {
+    /// Slot of the scope that's the depth above from the current one
+    Slot(usize, usize, String),
+    /// Variable that's found by the name at runtime, like builtins and global ones
+    Name(String),
+}
+
+impl Var {
+    fn get(&self, scope: &Scope) -> Option<Type> {
+        match self {
+            Var::Slot(depth, index, name) => scope.slot(*depth, *index, name),
+            Var::Name(name) => scope.get(name),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Var::Slot(_, _, name) | Var::Name(name) => name,
+        }
+    }
+}
+
+/// Instruction of the VM, that takes values from the stack and pushes the result
+enum Op {
+    Const(usize),
+    /// Value of the variable, forcing it if it's suspended
+    Load(Var),
+    /// Define the variable by the value on the top, that's left as the result
+    Define(String),
+    Set(String),
+    Pop,
+    Jump(usize),
+    JumpUnless(usize),
+    /// Scope of the local variables in the layout, like `let`
+    Enter(Rc<[String]>),
+    Leave,
+    /// Set the value to the slot of the current scope
+    Bind(usize),
+    /// Bind the variables of the pattern to the value on the top, or jump if it doesn't match
+    Match(usize, usize),
+    /// Bind the variables of the pattern to the value that's taken, it's error if it doesn't match
+    Destructure(usize),
+    /// Raise the error that no pattern matches the value on the top
+    Unmatched,
+    /// Argument that's evaluated when it's needed, or right now if it's cheap
+    Suspend(usize),
+    Delay(usize),
+    Closure(usize),
+    /// Builtin that's called with the values, it's for the cheap ones that evaluate all arguments
+    Apply(usize, usize),
+    /// Call the function on the top, whether it's in tail position
+    Call(usize, bool),
+    /// Code that isn't compiled is evaluated by `Type::eval`
+    Eval(usize, bool),
+    Return,
+}
+
+/// Code that's compiled from the function's body or the expression
+struct Code {
+    ops: Vec<Op>,
+    /// Expression that each operation comes from, to locate errors of it
+    spans: Vec<Option<Span>>,
+    consts: Vec<Type>,
+    lazies: Vec<Lazy>,
+    calls: Vec<CallSite>,
+    protos: Vec<Proto>,
+    /// Variables of the function's scope, and the slots of its parameters in order
+    layout: Rc<[String]>,
+    params: Vec<usize>,
+    source: Type,
+}
+
+impl Code {
+    fn new(source: Type) -> Code {
+        Code {
+            ops: vec![],
+            spans: vec![],
+            consts: vec![],
+            lazies: vec![],
+            calls: vec![],
+            protos: vec![],
+            layout: Rc::new([]),
+            params: vec![],
+            source,
+        }
+    }
+}
+
+/// Variables that make the code cheap when they're already evaluated, or `None` if it's never cheap.
+/// The flag is set to the ones that should be builtins, see `Type::is_cheap`
+type Cheap = Option<Vec<(Var, bool)>>;
+
+/// Argument or value of the binding, that's compiled when it's suspended at first
+struct Lazy {
+    source: Type,
+    code: OnceCell<(Rc<Code>, Cheap)>,
+}
+
+impl Lazy {
+    fn new(source: &Type) -> Lazy {
+        Lazy {
+            source: source.clone(),
+            code: OnceCell::new(),
+        }
+    }
+
+    fn code(&self, scope: &Scope) -> &(Rc<Code>, Cheap) {
+        self.code.get_or_init(|| {
+            let mut compiler = Compiler::new(&self.source, vec![], scope);
+            let cheap = compiler.cheap(&self.source);
+            compiler.compile(&self.source, true);
+            (compiler.finish(), cheap)
+        })
+    }
+
+    /// Same as `Type::suspend`
+    fn suspend(&self, scope: &Scope) -> Type {
+        if let Type::Number(_) | Type::String(_) | Type::Bool(_) | Type::Null = self.source {
+            return self.source.clone();
+        }
+        let (code, cheap) = self.code(scope);
+        let is_cheap = cheap.as_ref().is_some_and(|vars| {
+            vars.iter().all(|(var, builtin)| match var.get(scope) {
+                Some(Type::Function(Function::BuiltIn(_))) => true,
+                Some(Type::Thunk(thunk)) => {
+                    !builtin && !matches!(thunk.value(), None | Some(Type::Stream(_)))
+                }
+                Some(Type::Stream(_)) | None => false,
+                Some(_) => !builtin,
+            })
+        });
+        if is_cheap {
+            match Machine::eval(code.clone(), scope) {
+                Ok(value) if !matches!(value, Type::Thunk(_)) => return value,
+                _ => {}
+            }
+        }
+        Type::Thunk(Thunk::compiled(code.clone(), scope.clone()))
+    }
+}
+
+/// Function call that's compiled, the arguments are passed as they're written to builtins
+struct CallSite {
+    expr: Vec<Type>,
+    span: Option<Span>,
+    args: Vec<usize>,
+}
+
+/// Function that's made at runtime, closures made by the same code share the compiled body
+struct Proto {
+    name: Option<String>,
+    args: Vec<Type>,
+    body: Vec<Type>,
+    code: Rc<OnceCell<Rc<Code>>>,
+}
+
+impl Proto {
+    fn instantiate(&self, scope: &Scope) -> Result<Type, LazoError> {
+        let closure = Closure::new(self.name.clone(), &self.args, &self.body, scope)?;
+        Ok(Type::Function(Function::UserDefined(Rc::new(Closure {
+            code: self.code.clone(),
+            ..closure
+        }))))
+    }
+}
+
+/// Compiler from the code to the bytecode, where local variables are resolved to the slots.
+/// Special forms are compiled to jumps and scopes unless their names are overwritten
+struct Compiler {
+    code: Code,
+    /// Layouts of the scopes that the code makes, the innermost one is the last
+    layouts: Vec<Vec<String>>,
+    /// Scope where the code runs, that's outside of the layouts
+    env: Scope,
+    /// Innermost expression that's being compiled
+    span: Option<Span>,
+}
+
+impl Compiler {
+    fn new(source: &Type, layouts: Vec<Vec<String>>, env: &Scope) -> Compiler {
+        Compiler {
+            code: Code::new(source.clone()),
+            layouts,
+            env: env.clone(),
+            span: None,
+        }
+    }
+
+    /// Code of the expression that's evaluated in the scope
+    fn expression(expr: &Type, env: &Scope) -> Rc<Code> {
+        let mut compiler = Compiler::new(expr, vec![], env);
+        compiler.compile(expr, true);
+        compiler.finish()
+    }
+
+    /// Code of the function's body, whose scope has the parameters and the definitions in it
+    fn function(args: &[Param], body: &[Type], env: &Scope) -> Rc<Code> {
+        // Argument of the list parameter is kept in the slot of its code, that's never variable name
+        let mut layout: Vec<String> = vec![];
+        for (arg, _) in args {
+            if !layout.contains(&arg.get_string()) {
+                layout.push(arg.get_string());
+            }
+        }
+        let params: Vec<usize> = args
+            .iter()
+            .map(|(arg, _)| {
+                layout
+                    .iter()
+                    .position(|i| *i == arg.get_string())
+                    .unwrap_or(0)
+            })
+            .collect();
+        let patterns: Vec<(usize, &Type)> = args
+            .iter()
+            .zip(&params)
+            .filter(|((arg, _), _)| matches!(arg, Type::List(_)))
+            .map(|((arg, _), slot)| (*slot, arg))
+            .collect();
+        for (_, pattern) in &patterns {
+            for name in pattern_vars(pattern).unwrap_or_default() {
+                if !layout.contains(&name) {
+                    layout.push(name);
+                }
+            }
+        }
+        Compiler::definitions(body, &mut layout);
+
+        let source = Type::Expr(body.to_vec(), None);
+        let mut compiler = Compiler::new(&source, vec![layout.clone()], env);
+        for (slot, pattern) in patterns {
+            compiler.emit(Op::Load(Var::Slot(0, slot, pattern.get_string())));
+            compiler.code.consts.push(pattern.clone());
+            compiler.emit(Op::Destructure(compiler.code.consts.len() - 1));
+        }
+        compiler.block(body, true);
+        compiler.code.layout = layout.into();
+        compiler.code.params = params;
+        compiler.finish()
+    }
+
+    fn finish(mut self) -> Rc<Code> {
+        self.emit(Op::Return);
+        Rc::new(self.code)
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.code.ops.push(op);
+        self.code.spans.push(self.span.clone());
+        self.code.ops.len() - 1
+    }
+
+    /// Point the jump to the next operation
+    fn patch(&mut self, jump: usize) {
+        let next = self.code.ops.len();
+        match &mut self.code.ops[jump] {
+            Op::Jump(to) | Op::JumpUnless(to) | Op::Match(_, to) => *to = next,
+            _ => {}
+        }
+    }
+
+    fn constant(&mut self, value: Type) {
+        self.code.consts.push(value);
+        self.emit(Op::Const(self.code.consts.len() - 1));
+    }
+
+    fn lazy(&mut self, source: &Type) -> usize {
+        self.code.lazies.push(Lazy::new(source));
+        self.code.lazies.len() - 1
+    }
+
+    /// Names that the body defines in its scope, they get the slots in advance
+    fn definitions(body: &[Type], layout: &mut Vec<String>) {
+        for line in body {
+            let name = match line.get_list().as_slice() {
+                [Type::Symbol(form), Type::Symbol(name), _] if form == "define" => name.clone(),
+                [Type::Symbol(form), Type::Expr(head, _) | Type::List(head), _, ..]
+                    if form == "define" && !head.is_empty() =>
+                {
+                    head[0].get_string()
+                }
+                [Type::Symbol(form), block @ ..] if form == "begin" => {
+                    Compiler::definitions(block, layout);
+                    continue;
+                }
+                _ => continue,
+            };
+            if !matches!(line, Type::Expr(..)) || layout.contains(&name) {
+                continue;
+            }
+            layout.push(name);
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Var {
+        for (depth, layout) in self.layouts.iter().rev().enumerate() {
+            if let Some(index) = layout.iter().position(|i| i == name) {
+                return Var::Slot(depth, index, name.to_string());
+            }
+        }
+        let mut depth = self.layouts.len();
+        let mut scope = Some(self.env.clone());
+        while let Some(current) = scope {
+            let env = current.0.borrow();
+            if let Some(index) = env.position(name) {
+                return Var::Slot(depth, index, name.to_string());
+            }
+            scope = env.parent.clone();
+            depth += 1;
+        }
+        Var::Name(name.to_string())
+    }
+
+    fn cheap(&self, expr: &Type) -> Cheap {
+        match expr {
+            Type::Number(_) | Type::String(_) | Type::Bool(_) | Type::Null => Some(vec![]),
+            Type::Symbol(name) => Some(vec![(self.resolve(name), false)]),
+            Type::Expr(list, _) => match list.split_first() {
+                Some((Type::Symbol(name), args)) if CHEAP_BUILTINS.contains(&name.as_str()) => {
+                    let mut vars = vec![(self.resolve(name), true)];
+                    for arg in args {
+                        vars.extend(self.cheap(arg)?);
+                    }
+                    Some(vars)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn compile(&mut self, expr: &Type, tail: bool) {
+        match expr {
+            Type::Symbol(name) => {
+                let var = self.resolve(name);
+                self.emit(Op::Load(var));
+            }
+            Type::Expr(list, span) => {
+                let outer = std::mem::replace(&mut self.span, span.clone());
+                self.call(list, tail);
+                self.span = outer;
+            }
+            value => self.constant(value.clone()),
+        }
+    }
+
+    /// Evaluate the lines in order, the last one is in tail position
+    fn block(&mut self, block: &[Type], tail: bool) {
+        let Some((last, block)) = block.split_last() else {
+            return self.constant(Type::Null);
+        };
+        for line in block {
+            self.compile(line, false);
+            self.emit(Op::Pop);
+        }
+        self.compile(last, tail);
+    }
+
+    fn call(&mut self, list: &[Type], tail: bool) {
+        if let Some(Type::Symbol(name)) = list.first() {
+            // Builtins and macros are known here unless it's local variable
+            if let Var::Name(_) = self.resolve(name) {
+                match self.env.get(name) {
+                    Some(Type::Function(Function::BuiltIn(func))) if func.name == *name => {
+                        if self.special(name, &list[1..], tail) {
+                            return;
+                        }
+                        if CHEAP_BUILTINS.contains(&name.as_str())
+                            && func.arity.accepts(list.len() - 1)
+                        {
+                            for arg in &list[1..] {
+                                self.compile(arg, false);
+                            }
+                            self.code
+                                .consts
+                                .push(Type::Function(Function::BuiltIn(func)));
+                            self.emit(Op::Apply(self.code.consts.len() - 1, list.len() - 1));
+                            return;
+                        }
+                    }
+                    Some(Type::Function(Function::Macro(mac))) => {
+                        // Local variables aren't known until the code runs
+                        let shadowed = |name: &str, value: &Type| match self.resolve(name) {
+                            Var::Slot(depth, ..) if depth < self.layouts.len() => true,
+                            _ => self.env.get(name).as_ref() != Some(value),
+                        };
+                        if let Ok(expanded) = mac.expand(list, shadowed) {
+                            return self.compile(&expanded, tail);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let Some(head) = list.first() else {
+            self.code
+                .consts
+                .push(Type::Expr(list.to_vec(), self.span.clone()));
+            self.emit(Op::Eval(self.code.consts.len() - 1, tail));
+            return;
+        };
+        self.compile(head, false);
+        let args = list[1..].iter().map(|arg| self.lazy(arg)).collect();
+        self.code.calls.push(CallSite {
+            expr: list.to_vec(),
+            span: self.span.clone(),
+            args,
+        });
+        self.emit(Op::Call(self.code.calls.len() - 1, tail));
+    }
+
+    /// Compile the special form, or it's called as builtin if the form is wrong
+    fn special(&mut self, name: &str, args: &[Type], tail: bool) -> bool {
+        match (name, args) {
+            ("quote", [value]) => self.constant(value.clone()),
+            ("if", [cond, then, otherwise @ ..]) if otherwise.len() <= 1 => {
+                self.compile(cond, false);
+                let jump = self.emit(Op::JumpUnless(0));
+                self.compile(then, tail);
+                let end = self.emit(Op::Jump(0));
+                self.patch(jump);
+                match otherwise {
+                    [otherwise] => self.compile(otherwise, tail),
+                    _ => self.constant(Type::Null),
+                }
+                self.patch(end);
+            }
+            ("cond", clauses)
+                if clauses
+                    .iter()
+                    .all(|i| matches!(i, Type::Expr(i, _) | Type::List(i) if i.len() >= 2)) =>
+            {
+                let mut ends = vec![];
+                let mut otherwise = false;
+                for clause in clauses {
+                    let clause = clause.get_list();
+                    if matches!(&clause[0], Type::Symbol(name) if name == "else") {
+                        self.compile(&clause[1], tail);
+                        otherwise = true;
+                        break;
+                    }
+                    self.compile(&clause[0], false);
+                    let next = self.emit(Op::JumpUnless(0));
+                    self.compile(&clause[1], tail);
+                    ends.push(self.emit(Op::Jump(0)));
+                    self.patch(next);
+                }
+                if !otherwise {
+                    self.constant(Type::Null);
+                }
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+            ("begin", block) => self.block(block, tail),
+            ("match", [value, clauses @ ..]) => {
+                let Ok(clauses) = clauses.iter().map(clause).collect::<Result<Vec<_>, _>>() else {
+                    return false;
+                };
+                self.compile(value, false);
+                let mut ends = vec![];
+                for (pattern, guard, body) in clauses {
+                    let vars = pattern_vars(pattern).unwrap_or_default();
+                    self.enter(&vars.iter().collect::<Vec<_>>(), body);
+                    self.code.consts.push(pattern.clone());
+                    let mut fails = vec![self.emit(Op::Match(self.code.consts.len() - 1, 0))];
+                    if let Some(guard) = guard {
+                        self.compile(guard, false);
+                        fails.push(self.emit(Op::JumpUnless(0)));
+                    }
+                    // Matched value isn't needed anymore
+                    self.emit(Op::Pop);
+                    self.block(body, tail);
+                    self.leave();
+                    ends.push(self.emit(Op::Jump(0)));
+                    for fail in fails {
+                        self.patch(fail);
+                    }
+                    self.emit(Op::Leave);
+                }
+                self.emit(Op::Unmatched);
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+            ("define", [Type::Symbol(var), value]) => {
+                let lazy = self.lazy(value);
+                self.emit(Op::Suspend(lazy));
+                self.emit(Op::Define(var.clone()));
+            }
+            ("define", [Type::Expr(head, _) | Type::List(head), body @ ..]) if !head.is_empty() => {
+                self.proto(Some(head[0].get_string()), &head[1..], body);
+                self.emit(Op::Define(head[0].get_string()));
+            }
+            ("lambda", [Type::Expr(params, _) | Type::List(params), body @ ..])
+                if !body.is_empty() =>
+            {
+                self.proto(None, params, body)
+            }
+            ("let" | "let*" | "letrec", [binds, body @ ..]) => {
+                let Ok(binds) = bindings(binds) else {
+                    return false;
+                };
+                match name {
+                    "let" => self.bind_let(&binds, body, tail),
+                    "let*" => self.bind_sequential(&binds, body, tail),
+                    _ => self.bind_recursive(&binds, body, tail),
+                }
+            }
+            ("set!", [Type::Symbol(var), value]) => {
+                self.compile(value, false);
+                self.emit(Op::Set(var.clone()));
+            }
+            ("delay", [value]) => {
+                let lazy = self.lazy(value);
+                self.emit(Op::Delay(lazy));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn proto(&mut self, name: Option<String>, args: &[Type], body: &[Type]) {
+        self.code.protos.push(Proto {
+            name,
+            args: args.to_vec(),
+            body: body.to_vec(),
+            code: Rc::default(),
+        });
+        self.emit(Op::Closure(self.code.protos.len() - 1));
+    }
+
+    /// Scope of the names and the definitions of the body, the latter binding wins on the same name
+    fn enter(&mut self, names: &[&String], body: &[Type]) {
+        let mut layout: Vec<String> = vec![];
+        for name in names {
+            if !layout.contains(name) {
+                layout.push(name.to_string());
+            }
+        }
+        Compiler::definitions(body, &mut layout);
+        self.emit(Op::Enter(layout.clone().into()));
+        self.layouts.push(layout);
+    }
+
+    fn leave(&mut self) {
+        self.layouts.pop();
+        self.emit(Op::Leave);
+    }
+
+    fn slot(&self, name: &str) -> usize {
+        let layout = self.layouts.last().map(Vec::as_slice).unwrap_or_default();
+        layout.iter().position(|i| i == name).unwrap_or(0)
+    }
+
+    fn bind_let(&mut self, binds: &[(String, Type)], body: &[Type], tail: bool) {
+        for (_, value) in binds {
+            let lazy = self.lazy(value);
+            self.emit(Op::Suspend(lazy));
+        }
+        self.enter(
+            &binds.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            body,
+        );
+        for (index, (name, _)) in binds.iter().enumerate().rev() {
+            if binds[index + 1..].iter().any(|(later, _)| later == name) {
+                self.emit(Op::Pop);
+            } else {
+                self.emit(Op::Bind(self.slot(name)));
+            }
+        }
+        self.block(body, tail);
+        self.leave();
+    }
+
+    fn bind_sequential(&mut self, binds: &[(String, Type)], body: &[Type], tail: bool) {
+        for (index, (name, value)) in binds.iter().enumerate() {
+            let lazy = self.lazy(value);
+            self.emit(Op::Suspend(lazy));
+            // Definitions of the body are in the innermost scope
+            let last = index == binds.len() - 1;
+            self.enter(&[name], if last { body } else { &[] });
+            self.emit(Op::Bind(0));
+        }
+        self.block(body, tail);
+        for _ in binds {
+            self.leave();
+        }
+    }
+
+    fn bind_recursive(&mut self, binds: &[(String, Type)], body: &[Type], tail: bool) {
+        self.enter(
+            &binds.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            body,
+        );
+        for (name, value) in binds {
+            let lazy = self.lazy(value);
+            self.emit(Op::Suspend(lazy));
+            self.emit(Op::Bind(self.slot(name)));
+        }
+        self.block(body, tail);
+        self.leave();
+    }
+}
+
+/// Stack machine that runs the compiled code, as the function call at the depth of the call stack
+struct Machine {
+    code: Rc<Code>,
+    pc: usize,
+    stack: Vec<Type>,
+    scope: Scope,
+    depth: usize,
+    /// Call in tail position that the running code comes from,
+    /// errors that're not in any expression of the code are located at it
+    span: Option<Span>,
+}
+
+impl Machine {
+    fn new(code: Rc<Code>, scope: Scope, depth: usize) -> Machine {
+        Machine {
+            code,
+            pc: 0,
+            stack: vec![],
+            scope,
+            depth,
+            span: None,
+        }
+    }
+
+    /// Value of the code, that's the same as `Type::eval`
+    fn eval(code: Rc<Code>, scope: &Scope) -> Result<Type, LazoError> {
+        let runtime = scope.runtime();
+        let depth = runtime.call_stack.borrow().len();
+        settle(
+            Machine::new(code, scope.clone(), depth).run(),
+            depth,
+            &runtime,
+        )
+    }
+
+    /// Run the code, the code in tail position that isn't compiled is returned as `Type::Tail`
+    fn run(mut self) -> Result<Type, LazoError> {
+        loop {
+            let code = self.code.clone();
+            let pc = self.pc;
+            self.pc += 1;
+            match self.step(&code, pc) {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => {}
+                // Tail call switches the code only when it succeeds
+                Err(err) => return Err(err.at(code.spans[pc].clone()).at(self.span.clone())),
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Type {
+        self.stack.pop().unwrap_or(Type::Null)
+    }
+
+    fn step(&mut self, code: &Code, pc: usize) -> Result<Option<Type>, LazoError> {
+        match &code.ops[pc] {
+            Op::Const(index) => self.stack.push(code.consts[*index].clone()),
+            Op::Load(var) => {
+                let value = match var.get(&self.scope) {
+                    Some(Type::Thunk(thunk)) => thunk.force()?,
+                    Some(value) => value,
+                    None => return Err(self.scope.unbound(var.name())),
+                };
+                self.stack.push(value);
+            }
+            Op::Define(name) => {
+                let value = self.stack.last().cloned().unwrap_or(Type::Null);
+                self.scope.insert(name.clone(), value);
+            }
+            Op::Set(name) => {
+                let value = match self.pop() {
+                    Type::Thunk(thunk) => Type::Thunk(Thunk::forced(Type::Thunk(thunk))),
+                    value => value,
+                };
+                self.scope.set(name, value.clone())?;
+                self.stack.push(value);
+            }
+            Op::Pop => {
+                self.pop();
+            }
+            Op::Jump(to) => self.pc = *to,
+            Op::JumpUnless(to) => {
+                if !self.pop().get_bool() {
+                    self.pc = *to;
+                }
+            }
+            Op::Enter(layout) => self.scope = Scope::with_layout(&self.scope, layout.clone()),
+            Op::Leave => {
+                let parent = self.scope.0.borrow().parent.clone();
+                self.scope = parent.unwrap_or(self.scope.clone());
+            }
+            Op::Bind(index) => {
+                let value = self.pop();
+                self.scope.0.borrow_mut().slots[*index] = Some(value);
+            }
+            Op::Match(pattern, fail) => {
+                let value = self.stack.last().cloned().unwrap_or(Type::Null);
+                let mut binds = vec![];
+                if destructure(&code.consts[*pattern], value, &mut binds)? {
+                    for (name, value) in binds {
+                        self.scope.insert(name, value);
+                    }
+                } else {
+                    self.pc = *fail;
+                }
+            }
+            Op::Destructure(pattern) => {
+                let value = self.pop();
+                bind_pattern(&code.consts[*pattern], value, &self.scope)?;
+            }
+            Op::Unmatched => return Err(LazoError::Match(self.pop())),
+            Op::Suspend(lazy) => {
+                let value = code.lazies[*lazy].suspend(&self.scope);
+                self.stack.push(value);
+            }
+            Op::Delay(lazy) => {
+                let (code, _) = code.lazies[*lazy].code(&self.scope);
+                let thunk = Thunk::compiled(code.clone(), self.scope.clone());
+                self.stack.push(Type::Thunk(thunk));
+            }
+            Op::Closure(proto) => {
+                let closure = code.protos[*proto].instantiate(&self.scope)?;
+                self.stack.push(closure);
+            }
+            Op::Apply(func, count) => {
+                let Type::Function(Function::BuiltIn(func)) = &code.consts[*func] else {
+                    unreachable!("only builtins are applied")
+                };
+                let args = self.stack.split_off(self.stack.len() - count);
+                let args = args.iter().map(Type::quote).collect();
+                self.scope.runtime().tick()?;
+                let value = (func.func)(args, &mut self.scope)?;
+                self.scope.runtime().check_size(&value)?;
+                self.stack.push(value);
+            }
+            Op::Call(site, tail) => return self.call(code, &code.calls[*site], *tail),
+            Op::Eval(expr, true) => return Ok(Some(code.consts[*expr].tail(&self.scope))),
+            Op::Eval(expr, false) => {
+                let value = code.consts[*expr].eval(&mut self.scope)?;
+                self.stack.push(value);
+            }
+            Op::Return => return Ok(Some(self.pop())),
+        }
+        Ok(None)
+    }
+
+    fn call(
+        &mut self,
+        code: &Code,
+        site: &CallSite,
+        tail: bool,
+    ) -> Result<Option<Type>, LazoError> {
+        let runtime = self.scope.runtime();
+        runtime.tick()?;
+        let depth = match tail {
+            true => self.depth,
+            false => runtime.call_stack.borrow().len(),
+        };
+        let func = match self.pop() {
+            Type::Function(Function::UserDefined(func)) => func,
+            func => {
+                let result = Type::apply(func, &site.expr, &site.span, &mut self.scope, depth);
+                return match result? {
+                    // Variable in tail position is loaded here, to locate its error at the call
+                    Type::Tail(next, mut scope) if tail && !matches!(*next, Type::Expr(..)) => {
+                        next.eval(&mut scope).map(Some)
+                    }
+                    value if tail => Ok(Some(value)),
+                    value => {
+                        self.stack.push(settle(Ok(value), depth, &runtime)?);
+                        Ok(None)
+                    }
+                };
+            }
+        };
+
+        if func.args.len() != site.args.len() {
+            return Err(LazoError::Arity(
+                func.name(),
+                site.args.len(),
+                Arity::Exact(func.args.len()),
+            ));
+        }
+        let args = func.args.iter().zip(&site.args).zip(&site.expr[1..]);
+        let args = args
+            .map(
+                |(((_, annotation), lazy), arg)| match checked(annotation, arg, &site.span) {
+                    Some(checked) => checked.suspend(&self.scope),
+                    None => code.lazies[*lazy].suspend(&self.scope),
+                },
+            )
+            .collect();
+
+        if tail && func.ret.is_none() {
+            // Replacing the running code instead of the recursion
+            let code = func.code();
+            enter_frame(&runtime, depth, func.name(), &site.span)?;
+            self.scope = func.bind(&code, args);
+            self.code = code;
+            self.pc = 0;
+            self.stack.clear();
+            self.span = site.span.clone();
+            Ok(None)
+        } else if tail {
+            func.enter(args, &site.span, depth).map(Some)
+        } else {
+            let value = settle(func.enter(args, &site.span, depth), depth, &runtime)?;
+            self.stack.push(value);
+            Ok(None)
+        }
+    }
+}
+
+/// What the type checker knows about the variable
+#[derive(Clone)]
+enum Static {
+    Value(Option<String>),
+    Function(Vec<Option<String>>, Option<String>),
+    Macro,
+}
+
+/// Static checker of type annotations, that finds mismatches before the program runs.
+/// Type that can't be inferred is unknown, and it's accepted anywhere for gradual typing
+#[derive(Clone, Default)]
+struct Checker {
+    vars: HashMap<String, Static>,
+    /// Record types that're defined already or by the program
+    records: HashSet<String>,
+    /// Program imports the modules, whose record types aren't known until they're loaded
+    imports: bool,
+}
+
+impl Checker {
+    fn check(program: &[Type], scope: &Scope) -> Result<(), LazoError> {
+        let mut checker = Checker {
+            records: scope.runtime().records.borrow().clone(),
+            ..Checker::default()
+        };
+        checker.infer_block(program)?;
+        Ok(())
+    }
+
+    /// Name of the builtin type or the record type that's known
+    fn type_name(&self, name: &str) -> Result<String, LazoError> {
+        let name = type_name(name)?;
+        if TYPE_NAMES.contains(&name.as_str()) || self.records.contains(&name) || self.imports {
+            Ok(name)
+        } else {
+            Err(LazoError::Syntax(format!("unknown type name `{name}`")))
+        }
+    }
+
+    /// Type names in the annotations of the pattern
+    fn check_pattern(&self, pattern: &Type) -> Result<(), LazoError> {
+        match pattern {
+            Type::List(items) => {
+                let (items, rest) = split_rest(items);
+                for item in items.iter().chain(rest) {
+                    self.check_pattern(item)?;
+                }
+                Ok(())
+            }
+            Type::Expr(list, _) => match list.as_slice() {
+                [inner, Type::Symbol(colon), Type::Symbol(name)] if colon == ":" => {
+                    self.type_name(name)?;
+                    self.check_pattern(inner)
+                }
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Register the functions first, so that they can be called before their definition
+    fn declare(&mut self, expr: &Type) -> Result<(), LazoError> {
+        let Type::Expr(list, span) = expr else {
+            return Ok(());
+        };
+        match list.as_slice() {
+            [Type::Symbol(form), Type::Expr(head, _), body @ ..]
+                if form == "define" && !head.is_empty() =>
+            {
+                let ((args, ret), _) =
+                    signature(&head[1..], body).map_err(|err| err.at(span.clone()))?;
+                let args = args.into_iter().map(|(_, annotation)| annotation).collect();
+                self.vars
+                    .insert(head[0].get_string(), Static::Function(args, ret));
+            }
+            [Type::Symbol(form), Type::Symbol(name), _] if form == "define-syntax" => {
+                self.vars.insert(name.to_owned(), Static::Macro);
+            }
+            [Type::Symbol(form), Type::Symbol(name), _] if form == "define-record" => {
+                self.records.insert(name.to_owned());
+            }
+            [Type::Symbol(form), ..] if form == "import" => self.imports = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn infer_block(&mut self, block: &[Type]) -> Result<Option<String>, LazoError> {
+        for expr in block {
+            self.declare(expr)?;
+        }
+        let mut result = None;
+        for expr in block {
+            result = self.infer(expr)?;
+        }
+        Ok(result)
+    }
+
+    fn infer(&mut self, expr: &Type) -> Result<Option<String>, LazoError> {
+        match expr {
+            Type::Expr(list, span) => self
+                .infer_call(list, span)
+                .map_err(|err| err.at(span.clone())),
+            Type::Symbol(name) => Ok(match self.vars.get(name) {
+                Some(Static::Value(known)) => known.clone(),
+                Some(Static::Function(..)) => Some("function".to_string()),
+                Some(Static::Macro) | None => None,
+            }),
+            Type::Thunk(_) | Type::Tail(..) => Ok(None),
+            value => Ok(Some(value.get_type())),
+        }
+    }
+
+    fn infer_call(
+        &mut self,
+        list: &[Type],
+        span: &Option<Span>,
+    ) -> Result<Option<String>, LazoError> {
+        let Some((Type::Symbol(name), args)) = list.split_first() else {
+            for expr in list {
+                self.infer(expr)?;
+            }
+            return Ok(None);
+        };
+        match (name.as_str(), self.vars.get(name).cloned()) {
+            // Arguments of the macro aren't always code
+            (_, Some(Static::Macro)) => Ok(None),
+            (_, Some(Static::Function(params, ret))) => {
+                for (index, arg) in args.iter().enumerate() {
+                    let actual = self.infer(arg)?;
+                    if let (Some(Some(expected)), Some(actual)) = (params.get(index), actual) {
+                        if !compatible(expected, &actual) {
+                            return Err(LazoError::Type(format!(
+                                "argument {} of `{name}` should be {expected}, but it's {actual}",
+                                index + 1
+                            ))
+                            .at(arg.span()));
+                        }
+                    }
+                }
+                Ok(ret)
+            }
+            // Variable that shadows the builtin
+            (_, Some(Static::Value(_))) => {
+                for arg in args {
+                    self.infer(arg)?;
+                }
+                Ok(None)
+            }
+            ("quote", _) => Ok(args.first().map(|datum| datum.get_type())),
+            ("quasiquote" | "syntax-rules" | "define-syntax", _) => Ok(None),
+            ("define-record", _) => {
+                let [Type::Symbol(name), fields] = args else {
+                    return Ok(None);
+                };
+                self.records.insert(name.to_owned());
+                let fields = fields.get_list();
+                let record = Some(name.to_owned());
+                let functions = [
+                    (
+                        format!("make-{name}"),
+                        vec![None; fields.len()],
+                        record.clone(),
+                    ),
+                    (format!("{name}?"), vec![None], Some("bool".to_string())),
+                ];
+                for (func, params, ret) in functions {
+                    self.vars.insert(func, Static::Function(params, ret));
+                }
+                for field in fields {
+                    let field = field.get_string();
+                    let with = Static::Function(vec![None, None], record.clone());
+                    self.vars.insert(format!("{name}-with-{field}"), with);
+                    let accessor = Static::Function(vec![None], None);
+                    self.vars.insert(format!("{name}-{field}"), accessor);
+                }
+                Ok(Some("function".to_string()))
+            }
+            ("define", _) => match args {
+                [Type::Expr(signature, _), body @ ..] if !signature.is_empty() => {
+                    let name = signature[0].get_string();
+                    let known = self.infer_function(Some(&name), &signature[1..], body, span)?;
+                    self.vars.insert(name, known);
+                    Ok(Some("function".to_string()))
+                }
+                [Type::Symbol(name), value] => {
+                    let known = match value.get_list().as_slice() {
+                        [Type::Symbol(form), params, body @ ..]
+                            if form == "lambda" && matches!(value, Type::Expr(..)) =>
+                        {
+                            self.infer_function(
+                                Some(name),
+                                &params.get_list(),
+                                body,
+                                &value.span(),
+                            )?
+                        }
+                        _ => Static::Value(self.infer(value)?),
+                    };
+                    self.vars.insert(name.to_owned(), known);
+                    self.infer(&Type::Symbol(name.to_owned()))
+                }
+                _ => Ok(None),
+            },
+            ("lambda", _) => {
+                if let Some((params, body)) = args.split_first() {
+                    self.infer_function(None, &params.get_list(), body, span)?;
+                }
+                Ok(Some("function".to_string()))
+            }
+            ("let" | "let*" | "letrec", _) => {
+                let Some((bindings, body)) = args.split_first() else {
+                    return Ok(None);
+                };
+                let mut inner = self.clone();
+                for binding in bindings.get_list() {
+                    if let [Type::Symbol(name), value] = binding.get_list().as_slice() {
+                        let known = inner.infer(value)?;
+                        inner.vars.insert(name.to_owned(), Static::Value(known));
+                    }
+                }
+                inner.infer_block(body)
+            }
+            ("begin", _) => self.infer_block(args),
+            ("match", _) => {
+                let Some((value, clauses)) = args.split_first() else {
+                    return Ok(None);
+                };
+                self.infer(value)?;
+                let mut results = vec![];
+                for clause in clauses.iter().filter_map(|i| clause(i).ok()) {
+                    let (pattern, guard, body) = clause;
+                    self.check_pattern(pattern)?;
+                    let mut inner = self.clone();
+                    for name in pattern_vars(pattern)? {
+                        inner.vars.insert(name, Static::Value(None));
+                    }
+                    if let Some(guard) = guard {
+                        inner.infer(guard)?;
+                    }
+                    results.push(inner.infer_block(body)?);
+                }
+                // It's known only if all clauses have the same type
+                Ok(match results.split_first() {
+                    Some((Some(first), rest)) if rest.iter().all(|i| i.as_ref() == Some(first)) => {
+                        Some(first.to_owned())
+                    }
+                    _ => None,
+                })
+            }
+            ("if", _) => {
+                let mut branches = vec![];
+                for arg in args {
+                    branches.push(self.infer(arg)?);
+                }
+                Ok(match branches.as_slice() {
+                    [_, Some(then), Some(other)] if then == other => Some(then.to_owned()),
+                    _ => None,
+                })
+            }
+            ("the", _) => match args {
+                [Type::Symbol(expected), value] => {
+                    let expected = self.type_name(expected)?;
+                    if let Some(actual) = self.infer(value)? {
+                        if !compatible(&expected, &actual) {
+                            return Err(LazoError::Type(format!(
+                                "expected {expected}, but it's {actual}"
+                            ))
+                            .at(value.span()));
+                        }
+                    }
+                    Ok(Some(expected))
+                }
+                _ => Ok(None),
+            },
+            ("cast", _) => {
+                for arg in args {
+                    self.infer(arg)?;
+                }
+                Ok(match args {
+                    [_, Type::Symbol(name)] => Some(name.to_owned()),
+                    _ => None,
+                })
+            }
+            (name, None) => {
+                for arg in args {
+                    self.infer(arg)?;
+                }
+                Ok(Checker::builtin(name).map(|i| i.to_string()))
+            }
+        }
+    }
+
+    /// Check the body of the function with the types of parameters,
+    /// and whether it returns the annotated type
+    fn infer_function(
+        &mut self,
+        name: Option<&str>,
+        params: &[Type],
+        body: &[Type],
+        span: &Option<Span>,
+    ) -> Result<Static, LazoError> {
+        let ((args, ret), body) = signature(params, body)?;
+        for (arg, annotation) in &args {
+            self.check_pattern(arg)?;
+            if let Some(annotation) = annotation {
+                self.type_name(annotation)?;
+            }
+        }
+        if let Some(ret) = &ret {
+            self.type_name(ret)?;
+        }
+        let known = Static::Function(
+            args.iter()
+                .map(|(_, annotation)| annotation.clone())
+                .collect(),
+            ret.clone(),
+        );
+
+        let mut inner = self.clone();
+        if let Some(name) = name {
+            inner.vars.insert(name.to_string(), known.clone());
+        }
+        for (arg, annotation) in args {
+            if let Type::List(_) = arg {
+                for name in pattern_vars(&arg)? {
+                    inner.vars.insert(name, Static::Value(None));
+                }
+            } else {
+                inner
+                    .vars
+                    .insert(arg.get_string(), Static::Value(annotation));
+            }
+        }
+        if let (Some(expected), Some(actual)) = (&ret, inner.infer_block(body)?) {
+            if !compatible(expected, &actual) {
+                return Err(LazoError::Type(format!(
+                    "`{}` should return {expected}, but it returns {actual}",
+                    name.unwrap_or("lambda")
+                ))
+                // Literal has no span, but it's the last token of the definition
+                .at(body
+                    .last()
+                    .and_then(|i| i.span())
+                    .or(span.as_ref().and_then(Span::last))));
+            }
+        }
+        Ok(known)
+    }
+
+    /// Type of the value that the builtin returns
+    fn builtin(name: &str) -> Option<&'static str> {
+        match name {
+            "+" | "-" | "*" | "/" | "%" | "^" | "len" | "exact->inexact" | "floor" | "round"
+            | "numerator" | "denominator" => Some("number"),
+            "concat" | "format" | "input" | "repeat" | "join" | "type" | "error-message" => {
+                Some("string")
+            }
+            "error-kind" => Some("symbol"),
+            "=" | "!=" | "eq?" | "equal?" | ">" | ">=" | "<" | "<=" | "&" | "|" | "!" | "lazy?" => {
+                Some("bool")
+            }
+            "reverse" | "split" | "keys" | "values" => Some("list"),
+            "range" | "iterate" | "cycle" => Some("stream"),
+            "assoc" | "dissoc" | "merge" => Some("map"),
+            "has?" => Some("bool"),
+            "print" | "debug" | "for" | "strict" => Some("null"),
+            "delay" => Some("thunk"),
+            _ => None,
+        }
+    }
+}
+
+/// Stream is also the list, that's realized when it's used as the list
+fn compatible(expected: &str, actual: &str) -> bool {
+    expected == "any"
+        || actual == "any"
+        || expected == actual
+        || (expected, actual) == ("list", "stream")
+}
+
+fn parse(token: Span) -> Result<Type, LazoError> {
+    let text = token.text();
+    Ok(
+        // Number case
+        if let Some(n) = Number::parse(text) {
+            Type::Number(n)
+        // Bool calse
+        } else if let Ok(b) = text.parse::<bool>() {
+            Type::Bool(b)
+        // Null calse
+        } else if text == "null" {
+            Type::Null
+        // String calse
+        } else if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+            Type::String(unescape(&token)?)
+        // Raw string calse
+        } else if text.len() >= 3 && text.starts_with("r\"") && text.ends_with('"') {
+            // Removing outer syntax
+            Type::String(text[2..text.len() - 1].to_string())
+        // Expression case
+        } else if text.starts_with('(') && text.ends_with(')') {
+            let mut list = vec![];
+            for i in tokenize(token.inner())? {
+                list.push(parse(i)?)
+            }
+            Type::Expr(list, Some(token))
+        // List case
+        } else if text.starts_with("[") && text.ends_with(']') {
+            let mut list = vec![];
+            for i in tokenize(token.inner())? {
+                list.push(parse(i)?)
+            }
+            Type::List(list)
+        // Map case like `{"a" 1 "b" 2}`
+        } else if text.starts_with("{") && text.ends_with('}') {
+            let mut list = vec![];
+            for i in tokenize(token.inner())? {
+                list.push(parse(i)?)
+            }
+            if list.len() % 2 != 0 {
+                return Err(LazoError::Syntax(
+                    "map should be pairs of the key and the value".to_string(),
+                )
+                .at(Some(token)));
+            }
+            Type::Map(
+                list.chunks(2)
+                    .map(|pair| (pair[0].key(), (pair[0].clone(), pair[1].clone())))
+                    .collect(),
+            )
+        // Quotation case like `'x` that's same as `(quote x)`
+        } else if let Some((prefix, name)) = [
+            ("'", "quote"),
+            ("`", "quasiquote"),
+            (",@", "unquote-splicing"),
+            (",", "unquote"),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| text.starts_with(prefix))
+        {
+            let quoted = parse(token.slice(token.start + prefix.len(), token.end))?;
+            Type::Expr(vec![Type::Symbol(name.to_string()), quoted], Some(token))
+        // Other case will be symbol
+        } else {
+            Type::Symbol(text.to_string())
+        },
+    )
+}
+
+/// Remove outer quotes of the string literal and process escape sequences in it
+fn unescape(token: &Span) -> Result<String, LazoError> {
+    let error = |message: &str| LazoError::Syntax(message.to_string()).at(Some(token.clone()));
+    let mut result = String::new();
+    let inner = token.inner();
+    let mut chars = inner.text().chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        result.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') => {
+                let code: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                code.strip_prefix('{')
+                    .and_then(|code| u32::from_str_radix(code, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or(error(
+                        "invalid unicode escape, it should be like `\\u{3042}`",
+                    ))?
+            }
+            Some(other) => return Err(error(&format!("unknown escape sequence `\\{other}`"))),
+            None => return Err(error("escape sequence is not completed")),
+        });
+    }
+    Ok(result)
+}
+
+fn tokenize(input: Span) -> Result<Vec<Span>, LazoError> {
+    let mut tokens: Vec<Span> = Vec::new();
+    let mut current_token: Option<usize> = None;
+    let mut in_parentheses: Vec<usize> = Vec::new();
+    let mut in_quote: Option<usize> = None;
+    let mut in_raw_quote = false;
+    let mut in_line_comment = false;
+    let mut in_block_comment: Vec<usize> = Vec::new();
+    let mut datum_comments: usize = 0;
+
+    // Pushing the token unless it's commented out by datum comment
+    let push_token = |tokens: &mut Vec<Span>, datum_comments: &mut usize, token: Span| {
+        if *datum_comments > 0 {
+            *datum_comments -= 1;
+        } else {
+            tokens.push(token);
+        }
+    };
+
+    let mut chars = input.text().char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let pos = input.start + index;
+        let next = chars.peek().map(|(_, c)| *c);
+
+        // Comments are kept in the token inside of parentheses,
+        // they are skipped when it's tokenized again
+        if in_line_comment {
+            in_line_comment = c != '\n';
+            continue;
+        }
+        if !in_block_comment.is_empty() {
+            if c == '|' && next == Some('#') {
+                chars.next();
+                in_block_comment.pop();
+            } else if c == '#' && next == Some('|') {
+                chars.next();
+                in_block_comment.push(pos);
+            }
+            continue;
+        }
+        if in_quote.is_some() {
+            match c {
+                // Escaped character never closes the quote
+                '\\' if !in_raw_quote => {
+                    chars.next();
+                }
+                '"' => in_quote = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '(' | '[' | '{' if in_quote.is_none() => {
+                current_token.get_or_insert(pos);
+                in_parentheses.push(pos);
+            }
+            ')' | ']' | '}' if in_quote.is_none() => {
+                current_token.get_or_insert(pos);
+                if in_parentheses.pop().is_none() {
+                    return Err(LazoError::Syntax(
+                        "there's duplicate end of the parentheses".to_string(),
+                    )
+                    .at(Some(input.slice(pos, pos + 1))));
+                }
+            }
+            ' ' | '　' | '\n' | '\t' | '\r' | ';' | '#'
+                if in_quote.is_none() && (c != '#' || matches!(next, Some('|' | ';'))) =>
+            {
+                if in_parentheses.is_empty() {
+                    if let Some(start) = current_token.take() {
+                        push_token(&mut tokens, &mut datum_comments, input.slice(start, pos));
+                    }
+                }
+                match (c, next) {
+                    (';', _) => in_line_comment = true,
+                    ('#', Some('|')) => {
+                        chars.next();
+                        in_block_comment.push(pos);
+                    }
+                    ('#', _) => {
+                        chars.next();
+                        if in_parentheses.is_empty() {
+                            datum_comments += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            '"' => {
+                current_token.get_or_insert(pos);
+                in_quote = Some(pos);
+
+                // Raw string is prefixed by `r` like `r"C:\path"`
+                let before = &input.source.code[..pos];
+                in_raw_quote = before.ends_with('r')
+                    && before[..pos - 1]
+                        .chars()
+                        .next_back()
+                        .is_none_or(|c| c.is_whitespace() || "([{".contains(c));
+            }
+            _ => {
+                current_token.get_or_insert(pos);
+            }
+        }
+    }
+
+    // Syntax error check
+    if let Some(pos) = in_quote {
+        return Err(
+            LazoError::Syntax("there's not end of the quote".to_string())
+                .at(Some(input.slice(pos, pos + 1))),
+        );
+    }
+    if let Some(pos) = in_block_comment.pop() {
+        return Err(
+            LazoError::Syntax("there's not end of the block comment".to_string())
+                .at(Some(input.slice(pos, pos + 2))),
+        );
+    }
+    if let Some(pos) = in_parentheses.pop() {
+        return Err(
+            LazoError::Syntax("there's not end of the parentheses".to_string())
+                .at(Some(input.slice(pos, pos + 1))),
+        );
+    }
+
+    if let Some(start) = current_token {
+        push_token(
+            &mut tokens,
+            &mut datum_comments,
+            input.slice(start, input.end),
+        );
+    }
+    Ok(tokens)
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::interpreters;
+
+/// Lines of the backtrace under the rendered error
+fn trace(code: &str) -> Vec<Vec<String>> {
+    interpreters()
+        .into_iter()
+        .map(|mut lazo| {
+            let err = lazo.eval_str(code).unwrap_err().to_string();
+            err.lines()
+                .filter(|line| line.starts_with("    at "))
+                .map(|line| line.trim().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn frames_of_the_calls() {
+    let code = "(define (a)\n  (+ 1 (b)))\n(define (b)\n  (+ 1 (error \"deep\")))\n(a)";
+    for lines in trace(code) {
+        assert_eq!(
+            lines,
+            [
+                "at b (<string>:4)",
+                "at a (<string>:2)",
+                "at <toplevel> (<string>:5)"
+            ]
+        );
+    }
+    let code = "(define (f n) (if (= n 0) (error \"bottom\") (+ 1 (f (- n 1)))))\n(f 2)";
+    for lines in trace(code) {
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3], "at <toplevel> (<string>:2)");
+    }
+}
+
+#[test]
+fn tail_calls_leave_no_frame() {
+    let code = "(define (a) (b))\n(define (b) (c 1))\n(define (c x)\n  (car x x))\n(a)";
+    // `c` is called by `b` that has replaced the frame of `a`
+    for lines in trace(code) {
+        assert_eq!(lines, ["at c (<string>:4)", "at <toplevel> (<string>:2)"]);
+    }
+}
+
+#[test]
+fn top_level_error_has_no_trace() {
+    for mut lazo in interpreters() {
+        let err = lazo.eval_str("(error \"top\")").unwrap_err().to_string();
+        assert!(!err.contains("    at "), "{err}");
+        // Caught error doesn't keep the calls that it went out of
+        let code = "(define (g) (+ 1 (error \"in g\"))) (try (g) (catch e (error-message e)))";
+        assert_eq!(
+            String::try_from(lazo.eval_str(code).unwrap()).unwrap(),
+            "in g"
+        );
+    }
+}
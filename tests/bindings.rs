@@ -0,0 +1,54 @@
+mod common;
+
+use common::{interpreters, number};
+use lazo::Type;
+
+#[test]
+fn let_binds_in_parallel() {
+    for mut lazo in interpreters() {
+        assert_eq!(number(&mut lazo, "(let ((x 1) (y 2)) (+ x y))"), 3);
+        // Values are evaluated in the outer scope
+        lazo.eval_str("(define x 10)").unwrap();
+        assert_eq!(number(&mut lazo, "(let ((x 1) (y x)) y)"), 10);
+        assert_eq!(number(&mut lazo, "(let () 5)"), 5);
+        // Binding is lazy like the argument
+        assert_eq!(number(&mut lazo, "(let ((unused (error \"no\"))) 1)"), 1);
+    }
+}
+
+#[test]
+fn let_star_and_letrec() {
+    for mut lazo in interpreters() {
+        assert_eq!(
+            number(&mut lazo, "(let* ((x 1) (y (+ x 1)) (x (* y 10))) x)"),
+            20
+        );
+        let code = "(letrec ((ev (lambda (n) (if (= n 0) true (od (- n 1))))) (od (lambda (n) (if (= n 0) false (ev (- n 1)))))) (ev 10))";
+        assert_eq!(lazo.eval_str(code).unwrap(), Type::Bool(true));
+    }
+}
+
+#[test]
+fn set_changes_the_nearest_binding() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define x 1) (define (f) (set! x 2))")
+            .unwrap();
+        assert_eq!(number(&mut lazo, "(begin (f) x)"), 2);
+        // Local binding shadows the global one
+        assert_eq!(number(&mut lazo, "(let ((x 5)) (set! x 6) x)"), 6);
+        assert_eq!(number(&mut lazo, "x"), 2);
+        assert_eq!(number(&mut lazo, "(set! x 7)"), 7);
+        let err = lazo.eval_str("(set! nope 1)").unwrap_err();
+        assert_eq!(err.kind(), "unbound-variable");
+    }
+}
+
+#[test]
+fn malformed_bindings() {
+    for mut lazo in interpreters() {
+        for code in ["(let (x) 1)", "(let ((1 2)) 1)", "(let* ((x)) x)"] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "syntax", "{code}");
+        }
+    }
+}
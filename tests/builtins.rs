@@ -0,0 +1,46 @@
+mod common;
+
+use common::{debug, interpreters, number};
+
+#[test]
+fn arity_and_docs() {
+    for mut lazo in interpreters() {
+        let err = lazo.eval_str("(car 1 2)").unwrap_err();
+        assert_eq!(err.kind(), "arity");
+        assert!(err
+            .to_string()
+            .starts_with("Arity Error! `car` takes 1 argument, but 2 passed"));
+        assert_eq!(
+            debug(&mut lazo, "(doc car)"),
+            "\"(car) takes 1 argument\\nFirst element of the list or the stream\""
+        );
+    }
+}
+
+#[test]
+fn length() {
+    for mut lazo in interpreters() {
+        assert_eq!(number(&mut lazo, "(len [1 2 3])"), 3);
+        assert_eq!(number(&mut lazo, "(len (range 4))"), 4);
+        // String is counted by the characters, not the bytes
+        assert_eq!(number(&mut lazo, "(len \"abc\")"), 3);
+        assert_eq!(number(&mut lazo, "(len \"héllo\")"), 5);
+        assert_eq!(number(&mut lazo, "(len \"\")"), 0);
+    }
+}
+
+#[test]
+fn empty_input_is_error() {
+    for mut lazo in interpreters() {
+        assert_eq!(debug(&mut lazo, "(cdr [1])"), "[]");
+        for code in ["(cdr [])", "(cdr (cdr (range 1)))"] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert!(err.to_string().contains("passed list is empty"), "{err}");
+        }
+        for code in ["(cond ())", "(cond (true))", "(cond (false 1) ())"] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "syntax", "{code}");
+        }
+        assert_eq!(number(&mut lazo, "(cond (false 1) (else 2))"), 2);
+    }
+}
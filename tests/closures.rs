@@ -0,0 +1,48 @@
+mod common;
+
+use common::{interpreters, number};
+
+#[test]
+fn captured_variables_outlive_the_call() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define (adder x) (lambda (y) (+ x y))) (define add5 (adder 5))")
+            .unwrap();
+        assert_eq!(number(&mut lazo, "(add5 1)"), 6);
+        assert_eq!(
+            number(&mut lazo, "(((lambda (a) (lambda (b) (* a b))) 3) 4)"),
+            12
+        );
+        // Each call makes its own environment
+        lazo.eval_str("(define add1 (adder 1))").unwrap();
+        assert_eq!(number(&mut lazo, "(+ (add1 0) (add5 0))"), 6);
+    }
+}
+
+#[test]
+fn lexical_not_dynamic_scope() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define x 1) (define (get-x) x) (define (shadow x) (get-x))")
+            .unwrap();
+        assert_eq!(number(&mut lazo, "(shadow 100)"), 1);
+        // Caller's local variables don't leak into the callee
+        let err = lazo
+            .eval_str("(define (callee) hidden) (define (caller hidden) (callee)) (caller 1)")
+            .unwrap_err();
+        assert_eq!(err.kind(), "unbound-variable");
+    }
+}
+
+#[test]
+fn counters_share_mutable_state() {
+    for mut lazo in interpreters() {
+        lazo.eval_str(
+            "(define (counter) (define n 0) (lambda () (set! n (+ n 1)) n)) (define a (counter)) (define b (counter))",
+        )
+        .unwrap();
+        assert_eq!(number(&mut lazo, "(begin (a) (a) (a))"), 3);
+        assert_eq!(number(&mut lazo, "(b)"), 1);
+        // Closures made in the same call see each other's updates
+        let code = "(define (pair) (define v 0) `[,(lambda () (set! v (+ v 1))) ,(lambda () v)]) (define p (pair)) ((car p)) ((car p)) ((car (cdr p)))";
+        assert_eq!(number(&mut lazo, code), 2);
+    }
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{debug, interpreters, number};
+
+#[test]
+fn line_comments() {
+    for mut lazo in interpreters() {
+        let code =
+            "; first line\n(define x 1) ; after the code\n(+ x\n  ; inside the expression\n  2)";
+        assert_eq!(number(&mut lazo, code), 3);
+    }
+}
+
+#[test]
+fn nested_block_comments() {
+    for mut lazo in interpreters() {
+        let code = "#| block #| nested |# still comment (error \"x\") |# (+ 1 #| inline |# 2)";
+        assert_eq!(number(&mut lazo, code), 3);
+        let err = lazo.eval_str("#| unclosed #| |#").unwrap_err();
+        assert_eq!(err.kind(), "syntax");
+        assert!(err.to_string().contains("<string>:1:1"), "{err}");
+    }
+}
+
+#[test]
+fn datum_comments() {
+    for mut lazo in interpreters() {
+        assert_eq!(debug(&mut lazo, "[1 #;2 3]"), "[1 3]");
+        assert_eq!(debug(&mut lazo, "[1 #;(error \"x\") #;[2 3] 4]"), "[1 4]");
+        assert_eq!(number(&mut lazo, "#;(error \"x\") 5"), 5);
+    }
+}
+
+#[test]
+fn comment_markers_in_strings() {
+    for mut lazo in interpreters() {
+        assert_eq!(
+            debug(&mut lazo, "[\"a ; b\" \"#| c |#\" r\"#;d\"]"),
+            "[\"a ; b\" \"#| c |#\" \"#;d\"]"
+        );
+    }
+}
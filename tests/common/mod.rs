@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+
+use lazo::{Engine, Interpreter};
+
+/// Interpreters of both engines, tests run the same code on each of them
+pub fn interpreters() -> Vec<Interpreter> {
+    [Engine::Vm, Engine::TreeWalker]
+        .into_iter()
+        .map(|engine| {
+            let mut lazo = Interpreter::new();
+            lazo.set_engine(engine);
+            lazo
+        })
+        .collect()
+}
+
+/// Value of the code that should be the integer
+pub fn number(lazo: &mut Interpreter, code: &str) -> i64 {
+    i64::try_from(lazo.eval_str(code).unwrap()).unwrap()
+}
+
+/// Value of the code as it's printed by `{:?}`
+pub fn debug(lazo: &mut Interpreter, code: &str) -> String {
+    format!("{:?}", lazo.eval_str(code).unwrap())
+}
+
+/// Value of the code that should be the string
+pub fn text(lazo: &mut Interpreter, code: &str) -> String {
+    String::try_from(lazo.eval_str(code).unwrap()).unwrap()
+}
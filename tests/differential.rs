@@ -0,0 +1,252 @@
+use std::process::Command;
+
+/// Output, errors and exit status of the code that's run by the engine
+fn run(code: &str, tree_walk: bool) -> (String, String, Option<i32>) {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_lazo"));
+    if tree_walk {
+        command.arg("--tree-walk");
+    }
+    let output = command
+        .args(["-l", code])
+        .output()
+        .expect("running lazo is fault");
+    (
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.code(),
+    )
+}
+
+/// Both of the VM and the tree-walker should give the same result, that's returned
+fn agree(code: &str) -> (String, String) {
+    let vm = run(code, false);
+    let tree_walker = run(code, true);
+    assert_eq!(vm, tree_walker, "engines disagree on `{code}`");
+    (vm.0, vm.1)
+}
+
+fn output(code: &str) -> String {
+    agree(code).0
+}
+
+fn error(code: &str) -> String {
+    agree(code).1
+}
+
+#[test]
+fn arithmetic_and_conditions() {
+    assert_eq!(
+        output("(define (fib n) (cond ((< n 2) n) (else (+ (fib (- n 1)) (fib (- n 2)))))) (print (fib 15))"),
+        "610"
+    );
+    assert_eq!(
+        output("(define (cls n) (cond ((= n 0) \"zero\") ((= n 1) \"one\"))) (print (cls 0) (cls 1) (cls 2))"),
+        "zeroonenull"
+    );
+    assert_eq!(
+        output("(define (f x) (if x 1)) (print (f true) (f false) (^ 2 70) (/ 1 3) (exact->inexact (/ 1 4)))"),
+        "1null11805916207174113034241/30.25"
+    );
+}
+
+#[test]
+fn tail_calls() {
+    assert_eq!(
+        output("(define (loop n acc) (if (= n 0) acc (loop (- n 1) (+ acc 1)))) (print (loop 20000 0))"),
+        "20000"
+    );
+    assert_eq!(
+        output(
+            "(define (tl n) (let ((m (- n 1))) (if (= m 0) \"done\" (tl m)))) (print (tl 20000))"
+        ),
+        "done"
+    );
+    assert_eq!(
+        output("(define (tc n) (cond ((= n 0) \"ok\") (else (begin (tc (- n 1)))))) (print (tc 20000))"),
+        "ok"
+    );
+    assert_eq!(
+        output("(define (ev n) (if (= n 0) true (od (- n 1)))) (define (od n) (if (= n 0) false (ev (- n 1)))) (print (ev 10001))"),
+        "false"
+    );
+}
+
+#[test]
+fn laziness() {
+    assert_eq!(
+        output("(define (const x y) x) (print (const 1 (error \"never\")))"),
+        "1"
+    );
+    assert_eq!(
+        output("(define (twice x) (+ x x)) (print (twice (+ (print \"once \") 2)))"),
+        "once 4"
+    );
+    assert_eq!(
+        output("(define (g x) `[,x ,(lazy? x)]) (print (g (+ 1 2)) (g (car [1])))"),
+        "[3 false][1 false]"
+    );
+    assert_eq!(
+        output("(define (f x) (delay (+ x 1))) (define p (f 1)) (print p \" \" (lazy? p) \" \" (force p) \" \" p)"),
+        "(delay (+ x 1)) true 2 2"
+    );
+    assert_eq!(
+        output("(define (never) (error \"no\")) (define (m) (define v (never)) 1) (print (m))"),
+        "1"
+    );
+}
+
+#[test]
+fn local_scopes() {
+    assert_eq!(
+        output("(define x 10) (define (f) (let ((x 1) (y x)) (define z (+ x y)) `[,x ,y ,z])) (print (f))"),
+        "[1 10 11]"
+    );
+    assert_eq!(
+        output("(define (g) (let* ((a 1) (b (+ a 1)) (a (* b 10))) (define c a) `[,a ,b ,c])) (print (g))"),
+        "[20 2 20]"
+    );
+    assert_eq!(
+        output("(define (h) (letrec ((ev (lambda (n) (if (= n 0) true (od (- n 1))))) (od (lambda (n) (if (= n 0) false (ev (- n 1)))))) (ev 11))) (print (h))"),
+        "false"
+    );
+    assert_eq!(
+        output("(define (dup) (let ((a 1) (a 2)) a)) (print (dup) (let () 5) (let* () 6))"),
+        "256"
+    );
+    assert_eq!(
+        output("(define (k) (let* () (define w 3)) w) (print (k))"),
+        "3"
+    );
+    assert_eq!(
+        output("(define (f) (begin (define a 1) (define (b) (+ a 1))) (b)) (print (f))"),
+        "2"
+    );
+}
+
+#[test]
+fn closures_and_mutation() {
+    assert_eq!(
+        output("(define (counter) (let ((n 0)) (lambda () (set! n (+ n 1)) n))) (define c (counter)) (c) (c) (print (c))"),
+        "3"
+    );
+    assert_eq!(
+        output("(define (acc) (define total 0) (for [1 2 3] (lambda (i) (set! total (+ total i)))) total) (print (acc))"),
+        "6"
+    );
+    assert_eq!(
+        output(
+            "(print (((lambda (x) (lambda (y) (* x y))) 3) 4) (map [1 2 3] (lambda (x) (* x x))))"
+        ),
+        "12[1 4 9]"
+    );
+}
+
+#[test]
+fn dynamic_definitions() {
+    assert_eq!(
+        output("(define (shadow x) (eval '(define x 99)) x) (print (shadow 1))"),
+        "99"
+    );
+    assert_eq!(
+        output("(define (dyn) (eval '(define fresh 5)) fresh) (print (dyn))"),
+        "5"
+    );
+    assert_eq!(output("(define (h) (define + -) (+ 5 3)) (print (h))"), "2");
+    assert_eq!(
+        output("(define (g if) (if 1 2 3)) (print (try (g +) (catch e (error-message e))))"),
+        "6"
+    );
+}
+
+#[test]
+fn macros() {
+    let swap =
+        "(define-syntax swap! (syntax-rules () ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))";
+    assert_eq!(
+        output(&format!(
+            "{swap} (define (f) (define p 1) (define q 2) (swap! p q) `[,p ,q]) (print (f))"
+        )),
+        "[2 1]"
+    );
+    assert_eq!(
+        output(&format!(
+            "{swap} (define (bad) (swap! 1)) (print (try (bad) (catch e (error-message e))))"
+        )),
+        "there's no syntax rule that matches `(swap! 1)`"
+    );
+    assert_eq!(
+        output("(define-syntax my-or (syntax-rules () ((_) false) ((_ e) e) ((_ e r ...) (let ((t e)) (if t t (my-or r ...)))))) (define (g t) (my-or false t)) (print (g 5) (my-or))"),
+        "5false"
+    );
+}
+
+#[test]
+fn typed_functions() {
+    assert_eq!(
+        output("(define (f (x : number)) -> string (concat x \"!\")) (print (f 1))"),
+        "1!"
+    );
+    let err = error("(define (g (x : number)) -> number (concat x \"!\")) (g 1)");
+    assert!(err.contains("`g` should return number"), "{err}");
+    let err = error("(define (f (x : number)) x) (define (g y) (f y)) (g \"s\")");
+    assert!(err.contains("expected number"), "{err}");
+}
+
+#[test]
+fn malformed_forms() {
+    for code in [
+        "(if)",
+        "(let (x) x)",
+        "(lambda)",
+        "()",
+        "(1 2)",
+        "(+)",
+        "(undefined-fn 1)",
+    ] {
+        let (out, _) = agree(&format!(
+            "(define (f) {code}) (print (try (f) (catch e (error-message e))))"
+        ));
+        assert!(!out.is_empty(), "`{code}` should fail");
+    }
+    let out = output("(define (l a) a) (print (try (l) (catch e (error-message e))))");
+    assert_eq!(out, "`l` takes 1 argument, but 0 passed");
+}
+
+#[test]
+fn error_locations() {
+    for code in [
+        "(define (f) y) (print (f))",
+        "(define (f x) (if x yy 1)) (print (+ 1 (f true)))",
+        "(define (g) (h)) (define (h) (car 1 2)) (define (f) (begin (print \"a\") (g))) (f)",
+        "(define (f n) (if (= n 0) (error 'boom n) (f (- n 1)))) (define (g) (+ 1 (f 3))) (g)",
+        "(define (a) (b)) (define (b) (c)) (define (c) (error \"deep\")) (define (top) (+ 1 (a))) (top)",
+        "(define (a x) (map x (lambda (i) (b i)))) (define (b i) (if (= i 2) (error (quote bad) i) i)) (print (a [1 2 3]))",
+        "(define (f) (let ((x 1)) zz)) (f)",
+        "(define (f) (eval 'yy)) (print (f))",
+        "(define (w) (set! undefined-thing 1)) (w)",
+        "(define (o) (lenn [1])) (o)",
+    ] {
+        let err = error(code);
+        assert!(err.contains("Error!"), "{err}");
+    }
+    let err = error("(define (f) (let ((x 1)) zz)) (f)");
+    assert!(
+        err.contains("(let ((x 1)) zz)") && err.contains("at f"),
+        "{err}"
+    );
+}
+
+#[test]
+fn strict_mode() {
+    let (out, err) = agree(
+        "(strict) (define (f x) (+ x 1)) (print (try (f \"a\") (catch e (error-kind e)))) (f \"b\")",
+    );
+    assert_eq!(out, "type");
+    assert!(err.contains("Type Error!"), "{err}");
+}
+
+#[test]
+fn exit_status() {
+    assert_eq!(run("(define (f) (exit 3)) (f)", false).2, Some(3));
+    assert_eq!(run("(define (f) (exit 3)) (f)", true).2, Some(3));
+}
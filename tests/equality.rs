@@ -0,0 +1,91 @@
+use std::process::Command;
+
+/// Print the result of the code by the interpreter
+fn eval(code: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_lazo"))
+        .args(["-l", &format!("(print {code})")])
+        .output()
+        .expect("running lazo is fault");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("output should be UTF-8")
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(eval("(= 1 1)"), "true");
+    assert_eq!(eval("(= 1 1.0)"), "true");
+    assert_eq!(eval("(= 1 2)"), "false");
+    assert_eq!(eval("(= 2 2 2)"), "true");
+    assert_eq!(eval("(= 2 2 3)"), "false");
+    assert_eq!(eval("(= (/ 0.0 0) (/ 0.0 0))"), "false");
+}
+
+#[test]
+fn different_types_are_not_equal() {
+    assert_eq!(eval("(= 1 \"1\")"), "false");
+    assert_eq!(eval("(= \"a\" 'a)"), "false");
+    assert_eq!(eval("(= true 1)"), "false");
+    assert_eq!(eval("(= null false)"), "false");
+    assert_eq!(eval("(= [1 2] '(1 2))"), "false");
+}
+
+#[test]
+fn strings_and_bools() {
+    assert_eq!(eval("(= \"abc\" (concat \"a\" \"bc\"))"), "true");
+    assert_eq!(eval("(= \"abc\" \"ABC\")"), "false");
+    assert_eq!(eval("(= true (> 2 1))"), "true");
+    assert_eq!(eval("(= null null)"), "true");
+}
+
+#[test]
+fn structures() {
+    assert_eq!(eval("(= [1 [2 \"3\"]] [1 [2 \"3\"]])"), "true");
+    assert_eq!(eval("(= [1 [2 3]] [1 [2 4]])"), "false");
+    assert_eq!(eval("(= [1 2] [1 2 3])"), "false");
+    assert_eq!(eval("(= '(+ 1 2) '(+ 1 2))"), "true");
+    assert_eq!(eval("(= {\"a\" 1 \"b\" 2} {\"b\" 2 \"a\" 1})"), "true");
+    assert_eq!(eval("(= {\"a\" 1} {\"a\" 2})"), "false");
+}
+
+#[test]
+fn functions() {
+    assert_eq!(eval("(= + +)"), "true");
+    assert_eq!(eval("(= + -)"), "false");
+    assert_eq!(eval("(= car cdr)"), "false");
+    assert_eq!(
+        eval("(begin (define (f x) x) (define (g x) x) (= f g))"),
+        "false"
+    );
+    assert_eq!(eval("(begin (define (f x) x) (= f f))"), "true");
+}
+
+#[test]
+fn not_equal() {
+    assert_eq!(eval("(!= 1 2)"), "true");
+    assert_eq!(eval("(!= 1 1)"), "false");
+    assert_eq!(eval("(!= 1 2 1)"), "true");
+    assert_eq!(eval("(!= 1 1 1)"), "false");
+    assert_eq!(eval("(!= 'a \"a\")"), "true");
+}
+
+#[test]
+fn identity_and_deep_equality() {
+    assert_eq!(eval("(eq? 1 1)"), "true");
+    assert_eq!(eval("(eq? 1 1.0)"), "false");
+    assert_eq!(eval("(eq? (/ 1 2) 0.5)"), "false");
+    assert_eq!(eval("(eq? 1.5 (+ 1 0.5))"), "true");
+    assert_eq!(eval("(eq? 'a 'a)"), "true");
+    assert_eq!(eval("(eq? [] [])"), "true");
+    assert_eq!(eval("(eq? [1 2] [1 2])"), "false");
+    assert_eq!(eval("(equal? [1 2] [1 2])"), "true");
+    assert_eq!(eval("(equal? {\"a\" [1]} {\"a\" [1]})"), "true");
+    assert_eq!(
+        eval("(begin (define p (delay 1)) (define q (delay 1)) (eq? p q))"),
+        "false"
+    );
+    assert_eq!(eval("(begin (define p (delay 1)) (eq? p p))"), "true");
+}
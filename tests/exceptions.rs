@@ -0,0 +1,72 @@
+mod common;
+
+use common::{debug, interpreters, number};
+
+#[test]
+fn catch_gets_the_error() {
+    for mut lazo in interpreters() {
+        assert_eq!(
+            debug(
+                &mut lazo,
+                "(try (error \"boom\") (catch e (error-message e)))"
+            ),
+            "\"boom\""
+        );
+        assert_eq!(
+            debug(
+                &mut lazo,
+                "(try (error 'not-found 42) (catch e `[,(error-kind e) ,(error-payload e)]))"
+            ),
+            "[not-found 42]"
+        );
+        // Value that's used instead when it fails
+        assert_eq!(number(&mut lazo, "(try (/ 1 0) 0)"), 0);
+        assert_eq!(number(&mut lazo, "(try 1 (catch e 2))"), 1);
+    }
+}
+
+#[test]
+fn kinds_of_errors() {
+    for mut lazo in interpreters() {
+        for (code, kind) in [
+            ("(car 1 2)", "arity"),
+            ("undefined", "unbound-variable"),
+            ("(error \"m\")", "runtime"),
+            ("(/ 1 0)", "runtime"),
+            ("(match 1 (2 2))", "match"),
+        ] {
+            let code = format!("(error-kind (try {code} (catch e e)))");
+            assert_eq!(debug(&mut lazo, &code), kind, "{code}");
+        }
+    }
+}
+
+#[test]
+fn finally_always_runs() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define log 0)").unwrap();
+        let code = "(try 1 (finally (set! log (+ log 1))))";
+        assert_eq!(number(&mut lazo, code), 1);
+        let code = "(try (error \"x\") (catch e 2) (finally (set! log (+ log 10))))";
+        assert_eq!(number(&mut lazo, code), 2);
+        // Error that isn't caught goes out after the cleanup
+        let err = lazo
+            .eval_str("(try (error \"x\") (finally (set! log (+ log 100))))")
+            .unwrap_err();
+        assert_eq!(err.message(), "x");
+        assert_eq!(number(&mut lazo, "log"), 111);
+    }
+}
+
+#[test]
+fn raise_throws_again() {
+    for mut lazo in interpreters() {
+        let code = "(try (try (error 'inner 1) (catch e (raise e))) (catch e (error-kind e)))";
+        assert_eq!(debug(&mut lazo, code), "inner");
+        let err = lazo.eval_str("(raise 5)").unwrap_err();
+        assert_eq!(err.kind(), "runtime");
+        assert_eq!(err.message(), "5");
+        let err = lazo.eval_str("(try 1 (oops e) (finally 2))").unwrap_err();
+        assert_eq!(err.kind(), "syntax");
+    }
+}
@@ -0,0 +1,91 @@
+use lazo::{Arity, Interpreter, Type};
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+#[test]
+fn eval_str_returns_last_value() {
+    let mut lazo = Interpreter::new();
+    let value = lazo.eval_str("(define x 20) (+ x 22)").unwrap();
+    assert_eq!(i64::try_from(value).unwrap(), 42);
+    assert_eq!(
+        String::try_from(lazo.eval_str("(concat \"a\" \"b\")").unwrap()).unwrap(),
+        "ab"
+    );
+}
+
+#[test]
+fn call_function_with_rust_values() {
+    let mut lazo = Interpreter::new();
+    lazo.eval_str("(define (add a b) (+ a b)) (define (first l) (car l))")
+        .unwrap();
+    let sum = lazo.call("add", vec![1.into(), 2.5.into()]).unwrap();
+    assert_eq!(f64::try_from(sum).unwrap(), 3.5);
+    let first = lazo.call("first", vec![vec!["a", "b"].into()]).unwrap();
+    assert_eq!(String::try_from(first).unwrap(), "a");
+    assert!(lazo.call("undefined", vec![]).is_err());
+}
+
+#[test]
+fn register_rust_closure() {
+    let mut lazo = Interpreter::new();
+    lazo.register("sum", Arity::AtLeast(0), "Sum of the integers", |args| {
+        let mut total = 0;
+        for arg in args {
+            total += i64::try_from(arg)?;
+        }
+        Ok(total.into())
+    });
+    let value = lazo.eval_str("(sum 1 (+ 1 1) 3)").unwrap();
+    assert_eq!(i64::try_from(value).unwrap(), 6);
+    let err = lazo.eval_str("(sum 1 \"2\")").unwrap_err();
+    assert_eq!(err.kind(), "type");
+}
+
+#[test]
+fn register_stateful_closure() {
+    let mut lazo = Interpreter::new();
+    let count = Rc::new(Cell::new(0));
+    let counter = count.clone();
+    lazo.register("tick", Arity::Exact(0), "Count the calls", move |_| {
+        counter.set(counter.get() + 1);
+        Ok(counter.get().into())
+    });
+    lazo.eval_str("(tick) (tick)").unwrap();
+    assert_eq!(count.get(), 2);
+
+    let err = lazo.eval_str("(tick 1)").unwrap_err();
+    assert_eq!(err.kind(), "arity");
+    assert!(err.to_string().contains("`tick` takes 0 arguments"));
+    let doc = lazo.eval_str("(doc tick)").unwrap();
+    assert_eq!(
+        String::try_from(doc).unwrap(),
+        "(tick) takes 0 arguments\nCount the calls"
+    );
+}
+
+#[test]
+fn convert_values() {
+    let mut lazo = Interpreter::new();
+    lazo.define("names", vec!["x", "y"]);
+    lazo.define("nothing", ());
+    let list = lazo.eval_str("names").unwrap();
+    assert_eq!(Vec::<String>::try_from(list).unwrap(), ["x", "y"]);
+    assert_eq!(
+        Option::<i64>::try_from(lazo.eval_str("nothing").unwrap()).unwrap(),
+        None
+    );
+    assert!(bool::try_from(lazo.eval_str("(> 2 1)").unwrap()).unwrap());
+    assert!(matches!(lazo.get("names"), Some(Type::List(_))));
+    assert!(i64::try_from(Type::from("1")).is_err());
+}
+
+#[test]
+fn eval_file() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(dir.join("twice.lz"), "(define (twice x) (* x 2))").unwrap();
+    std::fs::write(dir.join("main.lz"), "(import twice) (twice 21)").unwrap();
+    let mut lazo = Interpreter::new();
+    let value = lazo.eval_file(dir.join("main.lz")).unwrap();
+    assert_eq!(i64::try_from(value).unwrap(), 42);
+}
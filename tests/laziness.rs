@@ -0,0 +1,68 @@
+mod common;
+
+use common::interpreters;
+use std::time::{Duration, Instant};
+
+#[test]
+fn expensive_unused_argument_is_not_computed() {
+    for mut lazo in interpreters() {
+        let start = Instant::now();
+        let code = "(define (f x) 1) `[,(f (^ 7 300000000)) ,(f (* (^ 7 300000000) 7))]";
+        assert_eq!(format!("{:?}", lazo.eval_str(code).unwrap()), "[1 1]");
+        let code = "(define (g x y) y) (g (concat (^ 7 300000000) \"\") 2)";
+        assert_eq!(i64::try_from(lazo.eval_str(code).unwrap()).unwrap(), 2);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
+
+#[test]
+fn accumulator_chain_is_forced() {
+    for mut lazo in interpreters() {
+        let code =
+            "(define (fact n acc) (if (= n 0) acc (fact (- n 1) (* acc n)))) (> (fact 20000 1) 0)";
+        assert!(bool::try_from(lazo.eval_str(code).unwrap()).unwrap());
+    }
+}
+
+#[test]
+fn argument_is_evaluated_once_when_needed() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define calls 0) (define (tick) (set! calls (+ calls 1)) calls)")
+            .unwrap();
+        let code = "(define (twice x) (+ x x)) (twice (tick))";
+        assert_eq!(i64::try_from(lazo.eval_str(code).unwrap()).unwrap(), 2);
+        assert_eq!(i64::try_from(lazo.eval_str("calls").unwrap()).unwrap(), 1);
+        // Unused argument is never evaluated
+        let code = "(define (const x y) x) (const 1 (tick))";
+        assert_eq!(i64::try_from(lazo.eval_str(code).unwrap()).unwrap(), 1);
+        assert_eq!(i64::try_from(lazo.eval_str("calls").unwrap()).unwrap(), 1);
+        let code = "(const 1 (error \"never\"))";
+        assert_eq!(i64::try_from(lazo.eval_str(code).unwrap()).unwrap(), 1);
+    }
+}
+
+#[test]
+fn delay_and_force() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define calls 0) (define p (delay (begin (set! calls (+ calls 1)) 42)))")
+            .unwrap();
+        assert!(bool::try_from(lazo.eval_str("(lazy? p)").unwrap()).unwrap());
+        assert_eq!(i64::try_from(lazo.eval_str("calls").unwrap()).unwrap(), 0);
+        // Forced value is memoized
+        let code = "`[,(force p) ,(force p) ,calls ,(lazy? p)]";
+        assert_eq!(
+            format!("{:?}", lazo.eval_str(code).unwrap()),
+            "[42 42 1 false]"
+        );
+        // Forcing the value that's not delayed gives the value itself
+        assert_eq!(
+            i64::try_from(lazo.eval_str("(force 5)").unwrap()).unwrap(),
+            5
+        );
+        assert!(!bool::try_from(lazo.eval_str("(lazy? 5)").unwrap()).unwrap());
+        let err = lazo
+            .eval_str("(force (delay (error \"late\")))")
+            .unwrap_err();
+        assert!(err.to_string().contains("late"), "{err}");
+    }
+}
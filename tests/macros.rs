@@ -0,0 +1,59 @@
+mod common;
+
+use common::{debug, interpreters};
+
+#[test]
+fn syntax_rules() {
+    for mut lazo in interpreters() {
+        let code = "(define-syntax unless (syntax-rules () ((_ c body ...) (if c null (begin body ...)))))";
+        lazo.eval_str(code).unwrap();
+        assert_eq!(debug(&mut lazo, "(unless false 1 2)"), "2");
+        assert_eq!(debug(&mut lazo, "(unless true 1 2)"), "null");
+        let code = "(define-syntax my-or (syntax-rules () ((_) false) ((_ e) e) ((_ e r ...) (let ((t e)) (if t t (my-or r ...))))))";
+        lazo.eval_str(code).unwrap();
+        assert_eq!(debug(&mut lazo, "(my-or false null 3)"), "3");
+        assert_eq!(lazo.eval_str("(unless)").unwrap_err().kind(), "syntax");
+    }
+}
+
+#[test]
+fn introduced_variables_are_renamed() {
+    for mut lazo in interpreters() {
+        let code = "(define-syntax swap! (syntax-rules () ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))";
+        lazo.eval_str(code).unwrap();
+        let code = "(define tmp 1) (define other 2) (swap! tmp other) `[,tmp ,other]";
+        assert_eq!(debug(&mut lazo, code), "[2 1]");
+        // `t` of `my-or` doesn't capture the user's one
+        let code = "(define-syntax my-or (syntax-rules () ((_ a b) (let ((t a)) (if t t b))))) (define (f t) (my-or false t)) (f 5)";
+        assert_eq!(debug(&mut lazo, code), "5");
+    }
+}
+
+#[test]
+fn free_identifiers_are_not_captured() {
+    for mut lazo in interpreters() {
+        let code = "(define-syntax my-if (syntax-rules () ((_ c a b) (cond (c a) (else b)))))";
+        lazo.eval_str(code).unwrap();
+        // Local variables where it's used don't shadow the ones of the template
+        assert_eq!(
+            debug(&mut lazo, "(define (f cond) (my-if true 1 2)) (f 0)"),
+            "1"
+        );
+        assert_eq!(debug(&mut lazo, "(let ((cond 5)) (my-if false 1 2))"), "2");
+        let code = "(define (helper x) (* x 10)) (define-syntax tenfold (syntax-rules () ((_ e) (helper e)))) (define (g helper) (tenfold helper)) (g 4)";
+        assert_eq!(debug(&mut lazo, code), "40");
+        let code = "(define limit 7) (define-syntax get-limit (syntax-rules () ((_) limit))) (define (h limit) (get-limit)) (h 1)";
+        assert_eq!(debug(&mut lazo, code), "7");
+        // Quoted symbols are kept as they are
+        let code = "(define-syntax names (syntax-rules () ((_) '(cond limit)))) (define (k cond) (names)) (k 1)";
+        assert_eq!(debug(&mut lazo, code), "(cond limit)");
+    }
+}
+
+#[test]
+fn malformed_binding_in_template() {
+    for mut lazo in interpreters() {
+        let code = "(define-syntax bad (syntax-rules () ((_) (let (()) 1)))) (bad)";
+        assert_eq!(lazo.eval_str(code).unwrap_err().kind(), "syntax");
+    }
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::{debug, interpreters};
+use lazo::Type;
+
+#[test]
+fn map_builtins() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define m {\"a\" 1 \"b\" 2})").unwrap();
+        assert_eq!(debug(&mut lazo, "(get m \"a\")"), "1");
+        assert_eq!(debug(&mut lazo, "(get m \"z\" 0)"), "0");
+        assert_eq!(
+            debug(&mut lazo, "(assoc m \"c\" 3)"),
+            "{\"a\" 1 \"b\" 2 \"c\" 3}"
+        );
+        assert_eq!(debug(&mut lazo, "(dissoc m \"a\")"), "{\"b\" 2}");
+        assert_eq!(debug(&mut lazo, "(keys m)"), "[\"a\" \"b\"]");
+        assert_eq!(debug(&mut lazo, "(values m)"), "[1 2]");
+        assert_eq!(
+            debug(&mut lazo, "(merge m {\"b\" 20})"),
+            "{\"a\" 1 \"b\" 20}"
+        );
+        // Original map isn't changed
+        assert_eq!(debug(&mut lazo, "m"), "{\"a\" 1 \"b\" 2}");
+    }
+}
+
+#[test]
+fn keys_follow_equality() {
+    for mut lazo in interpreters() {
+        assert_eq!(debug(&mut lazo, "(get {1 \"one\"} 1.0)"), "\"one\"");
+        assert_eq!(debug(&mut lazo, "(get {0.5 \"half\"} (/ 1 2))"), "\"half\"");
+        assert_eq!(
+            debug(&mut lazo, "(get {[1 2] \"pair\"} `[1.0 ,(+ 1 1)])"),
+            "\"pair\""
+        );
+        assert_eq!(
+            debug(&mut lazo, "(assoc {1 \"a\"} 1.0 \"b\")"),
+            "{1.0 \"b\"}"
+        );
+        // Values of different types are different keys like `=`
+        assert_eq!(
+            lazo.eval_str("(has? {\"1\" 1} 1)").unwrap(),
+            Type::Bool(false)
+        );
+        assert_eq!(
+            lazo.eval_str("(has? {'a 1} \"a\")").unwrap(),
+            Type::Bool(false)
+        );
+    }
+}
+
+#[test]
+fn identity_keys() {
+    for mut lazo in interpreters() {
+        // Closures, streams, errors and delayed values are the same key only if they're the same one
+        for (value, other) in [
+            ("(lambda (x) x)", "(lambda (x) x)"),
+            ("(range)", "(range)"),
+            (
+                "(try (error \"e\") (catch e e))",
+                "(try (error \"e\") (catch e e))",
+            ),
+            ("(delay 1)", "(delay 1)"),
+        ] {
+            let code = format!(
+                "(define k {value}) (define m (assoc {{}} k 1)) `[,(get m k) ,(has? m {other})]"
+            );
+            assert_eq!(debug(&mut lazo, &code), "[1 false]", "{value}");
+        }
+        // Record types of the same name from the other definitions are different
+        let code = "(define-record point (x)) (define m (assoc {} (make-point 1) 1)) (len m) (define-record point (x)) `[,(has? m (make-point 1)) ,(len (keys (assoc m (make-point 1) 2)))]";
+        assert_eq!(debug(&mut lazo, code), "[false 2]");
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::interpreters;
+use std::path::PathBuf;
+
+/// Directory that has the modules of the test
+fn modules(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, code) in files {
+        std::fs::write(dir.join(file), code).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn export_limits_what_is_visible() {
+    let dir = modules(
+        "exports",
+        &[
+            (
+                "math.lz",
+                "(export square cube) (define (helper x) (* x x)) (define (square x) (helper x)) (define (cube x) (* x (helper x)))",
+            ),
+            ("main.lz", "(import math) `[,(square 3) ,(cube 2)]"),
+            ("hidden.lz", "(import math) (helper 3)"),
+            ("only.lz", "(import \"math.lz\" :only (square)) (cube 2)"),
+            ("missing.lz", "(import math :only (helper))"),
+        ],
+    );
+    for mut lazo in interpreters() {
+        let value = lazo.eval_file(dir.join("main.lz")).unwrap();
+        assert_eq!(format!("{value:?}"), "[9 8]");
+    }
+    // Each script has the fresh interpreter, not to see the names imported by the others
+    for (file, expected) in [
+        ("hidden.lz", "`helper` is not defined"),
+        ("only.lz", "`cube` is not defined"),
+        ("missing.lz", "doesn't export `helper`"),
+    ] {
+        for mut lazo in interpreters() {
+            let err = lazo.eval_file(dir.join(file)).unwrap_err();
+            assert!(err.to_string().contains(expected), "{file}: {err}");
+        }
+    }
+}
+
+#[test]
+fn modules_are_loaded_once() {
+    let dir = modules(
+        "caching",
+        &[
+            (
+                "counter.lz",
+                "(define n 0) (define (next) (set! n (+ n 1)) n)",
+            ),
+            ("user.lz", "(import counter) (define first (next))"),
+            (
+                "main.lz",
+                "(import counter) (import user) `[,first ,(next)]",
+            ),
+        ],
+    );
+    for mut lazo in interpreters() {
+        // Both of the importers share the state of the same module
+        let value = lazo.eval_file(dir.join("main.lz")).unwrap();
+        assert_eq!(format!("{value:?}"), "[1 2]");
+    }
+}
+
+#[test]
+fn circular_and_missing_imports() {
+    let dir = modules(
+        "cycles",
+        &[
+            ("a.lz", "(import b)"),
+            ("b.lz", "(import a)"),
+            ("main.lz", "(import nowhere)"),
+        ],
+    );
+    for mut lazo in interpreters() {
+        let err = lazo.eval_file(dir.join("a.lz")).unwrap_err();
+        assert!(err.to_string().contains("circular import"), "{err}");
+        let err = lazo.eval_file(dir.join("main.lz")).unwrap_err();
+        assert!(err.to_string().contains("is not found"), "{err}");
+    }
+}
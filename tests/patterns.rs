@@ -0,0 +1,93 @@
+mod common;
+
+use common::{interpreters, text};
+
+#[test]
+fn match_patterns() {
+    let describe = r#"
+        (define (describe v)
+          (match v
+            (0 "zero")
+            ("hi" "greeting")
+            ('sym "symbol")
+            ([] "empty")
+            ([x y] when (= x y) "same pair")
+            ([(x : number) . rest] (concat "number then " rest))
+            ([[a b] c] (concat a b c))
+            ((s : string) (concat "string " s))
+            (_ "other")))
+    "#;
+    for mut lazo in interpreters() {
+        lazo.eval_str(describe).unwrap();
+        for (code, expected) in [
+            ("(describe 0)", "zero"),
+            ("(describe \"hi\")", "greeting"),
+            ("(describe 'sym)", "symbol"),
+            ("(describe [])", "empty"),
+            ("(describe [2 2])", "same pair"),
+            ("(describe [2 3])", "number then [3]"),
+            ("(describe [1 2 3])", "number then [2 3]"),
+            ("(describe [[\"a\" \"b\"] \"c\"])", "abc"),
+            ("(describe \"s\")", "string s"),
+            ("(describe true)", "other"),
+        ] {
+            assert_eq!(text(&mut lazo, code), expected, "{code}");
+        }
+    }
+}
+
+#[test]
+fn recursion_over_patterns() {
+    for mut lazo in interpreters() {
+        let code = "(define (sum xs acc) (match xs ([] acc) ([x . rest] (sum rest (+ acc x)))))";
+        lazo.eval_str(code).unwrap();
+        // Clauses are in tail position
+        let sum = lazo.eval_str("(sum (take (range) 20000) 0)").unwrap();
+        assert_eq!(i64::try_from(sum).unwrap(), 199990000);
+        // Rest of the stream is also the stream
+        let code = "(match (range) ([a b . rest] `[,a ,b ,(car rest) ,(type rest)]))";
+        assert_eq!(
+            format!("{:?}", lazo.eval_str(code).unwrap()),
+            "[0 1 2 \"stream\"]"
+        );
+    }
+}
+
+#[test]
+fn destructuring_parameters() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define (first-two [a b . _]) (+ a b))")
+            .unwrap();
+        let value = lazo.eval_str("(first-two [10 20 30])").unwrap();
+        assert_eq!(i64::try_from(value).unwrap(), 30);
+        let value = lazo
+            .eval_str("((lambda ([a b] c) `[,b ,a ,c]) [1 2] 3)")
+            .unwrap();
+        assert_eq!(format!("{value:?}"), "[2 1 3]");
+        let value = lazo
+            .eval_str("(define (typed ([a b] : list)) (* a b)) (typed (range 3 5))")
+            .unwrap();
+        assert_eq!(i64::try_from(value).unwrap(), 12);
+        let err = lazo.eval_str("(first-two [1])").unwrap_err();
+        assert_eq!(err.kind(), "match");
+        assert!(lazo.eval_str("(define (bad [a (f b)]) a)").is_err());
+    }
+}
+
+#[test]
+fn no_match_is_error() {
+    for mut lazo in interpreters() {
+        let err = lazo.eval_str("(match 5 (\"x\" 1) ([y] y))").unwrap_err();
+        assert_eq!(err.kind(), "match");
+        assert_eq!(i64::try_from(err.payload()).unwrap(), 5);
+        let code = "(try (match [1 2] ([x] x)) (catch e (error-message e)))";
+        assert_eq!(
+            text(&mut lazo, code),
+            "there's no pattern that matches `[1 2]`"
+        );
+        assert_eq!(
+            lazo.eval_str("(match 1 ((f x) 1))").unwrap_err().kind(),
+            "syntax"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{debug, interpreters};
+
+#[test]
+fn quote_keeps_the_code() {
+    for mut lazo in interpreters() {
+        assert_eq!(debug(&mut lazo, "'(+ 1 2)"), "(+ 1 2)");
+        assert_eq!(debug(&mut lazo, "(quote (a [b c]))"), "(a [b c])");
+        assert_eq!(debug(&mut lazo, "(eval '(+ 1 2))"), "3");
+    }
+}
+
+#[test]
+fn unquote_and_splicing() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define x 5) (define xs [1 2])").unwrap();
+        assert_eq!(debug(&mut lazo, "`(+ ,x 1)"), "(+ 5 1)");
+        assert_eq!(debug(&mut lazo, "`(+ ,@xs ,x)"), "(+ 1 2 5)");
+        assert_eq!(debug(&mut lazo, "`[0 ,@xs ,(+ x 1)]"), "[0 1 2 6]");
+        assert_eq!(debug(&mut lazo, "`[,@[] ,@(range 2)]"), "[0 1]");
+        // Built expression can be evaluated
+        assert_eq!(debug(&mut lazo, "(eval `(* ,x ,@xs))"), "10");
+    }
+}
+
+#[test]
+fn nested_quasiquote() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define x 5)").unwrap();
+        // Inner unquote belongs to the inner quasiquote
+        assert_eq!(debug(&mut lazo, "``(a ,x)"), "(quasiquote (a (unquote x)))");
+        assert_eq!(
+            debug(&mut lazo, "``(a ,,x)"),
+            "(quasiquote (a (unquote 5)))"
+        );
+        assert_eq!(
+            debug(&mut lazo, "`[1 `[2 ,[3 ,x]]]"),
+            "[1 (quasiquote [2 (unquote [3 5])])]"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{debug, interpreters};
+use lazo::{Interpreter, Type};
+
+/// Interpreters of both engines that have `point` defined
+fn points() -> Vec<Interpreter> {
+    interpreters()
+        .into_iter()
+        .map(|mut lazo| {
+            lazo.eval_str("(define-record point (x y)) (define p (make-point 1 2))")
+                .unwrap();
+            lazo
+        })
+        .collect()
+}
+
+#[test]
+fn generated_functions() {
+    for mut lazo in points() {
+        assert_eq!(debug(&mut lazo, "p"), "#point{x: 1 y: 2}");
+        assert_eq!(debug(&mut lazo, "(type p)"), "\"point\"");
+        assert_eq!(
+            debug(&mut lazo, "`[,(point? p) ,(point? [1 2])]"),
+            "[true false]"
+        );
+        assert_eq!(debug(&mut lazo, "(+ (point-x p) (point-y p))"), "3");
+        // Updater makes the copy, the original one isn't changed
+        assert_eq!(
+            debug(&mut lazo, "`[,(point-with-x p 10) ,p]"),
+            "[#point{x: 10 y: 2} #point{x: 1 y: 2}]"
+        );
+        let err = lazo.eval_str("(point-x 5)").unwrap_err();
+        assert_eq!(err.kind(), "type");
+        assert!(lazo.eval_str("(make-point 1)").is_err());
+    }
+}
+
+#[test]
+fn field_wise_equality() {
+    for mut lazo in points() {
+        assert_eq!(
+            lazo.eval_str("(= p (make-point 1 2))").unwrap(),
+            Type::Bool(true)
+        );
+        assert_eq!(
+            lazo.eval_str("(= p (make-point 2 1))").unwrap(),
+            Type::Bool(false)
+        );
+        assert_eq!(
+            lazo.eval_str("(eq? p (make-point 1 2))").unwrap(),
+            Type::Bool(false)
+        );
+        // Other type with the same fields isn't equal
+        let code = "(define-record vec (x y)) (= p (make-vec 1 2))";
+        assert_eq!(lazo.eval_str(code).unwrap(), Type::Bool(false));
+    }
+}
+
+#[test]
+fn malformed_records() {
+    for mut lazo in points() {
+        for code in [
+            "(define-record (x y))",
+            "(define-record bad (x x))",
+            "(define-record bad (1))",
+        ] {
+            assert!(lazo.eval_str(code).is_err(), "{code}");
+        }
+    }
+}
+
+#[test]
+fn record_names_are_types() {
+    for mut lazo in points() {
+        lazo.eval_str("(define (norm (q : point)) (+ (point-x q) (point-y q)))")
+            .unwrap();
+        assert_eq!(debug(&mut lazo, "(norm p)"), "3");
+        let code = "(define (kind v) (match v ((q : point) \"point\") (_ \"other\")))";
+        lazo.eval_str(code).unwrap();
+        assert_eq!(
+            debug(&mut lazo, "`[,(kind p) ,(kind 5)]"),
+            "[\"point\" \"other\"]"
+        );
+        // Argument from untyped code is checked by the record's tag
+        let err = lazo
+            .eval_str("(define (g y) (norm y)) (g [1 2])")
+            .unwrap_err();
+        assert_eq!(err.kind(), "type");
+        assert!(lazo.eval_str("(define-record number (x))").is_err());
+    }
+}
+
+#[test]
+fn imported_record_types() {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("records");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("shapes.lz"), "(define-record circle (r))").unwrap();
+    // Checker doesn't know the record types of the module before it's loaded
+    let code = "(import shapes) (define (area (c : circle)) (* 3 (circle-r c) (circle-r c))) (area (make-circle 2))";
+    std::fs::write(dir.join("main.lz"), code).unwrap();
+    for mut lazo in interpreters() {
+        let value = lazo.eval_file(dir.join("main.lz")).unwrap();
+        assert_eq!(i64::try_from(value).unwrap(), 12);
+    }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::interpreters;
+use lazo::{Capability, Interpreter, Limits};
+use std::time::{Duration, Instant};
+
+/// Interpreters of both engines with the limits
+fn sandboxes(limits: Limits) -> Vec<Interpreter> {
+    interpreters()
+        .into_iter()
+        .map(|mut lazo| {
+            lazo.set_limits(limits);
+            lazo
+        })
+        .collect()
+}
+
+/// Kind of the error that the code raises
+fn violation(lazo: &mut Interpreter, code: &str) -> String {
+    match lazo.eval_str(code) {
+        Ok(value) => panic!("`{code}` should fail, but returned `{value:?}`"),
+        Err(err) => {
+            assert!(err.is_limit(), "{err}");
+            err.kind()
+        }
+    }
+}
+
+#[test]
+fn fuel() {
+    let limits = Limits {
+        fuel: Some(1000),
+        ..Limits::default()
+    };
+    for mut lazo in sandboxes(limits) {
+        assert_eq!(violation(&mut lazo, "(define (f) (f)) (f)"), "fuel");
+        // Fuel is filled again for the next run
+        assert!(lazo.eval_str("(+ 1 2)").is_ok());
+        assert_eq!(violation(&mut lazo, "(len (range))"), "fuel");
+        assert_eq!(
+            violation(&mut lazo, "(try (f) (catch e \"caught\"))"),
+            "fuel"
+        );
+    }
+}
+
+#[test]
+fn timeout() {
+    let limits = Limits {
+        timeout: Some(Duration::from_millis(200)),
+        ..Limits::default()
+    };
+    for mut lazo in sandboxes(limits) {
+        let start = Instant::now();
+        let code = "(define (f n) (f (+ n 1))) (f 0)";
+        assert_eq!(violation(&mut lazo, code), "timeout");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
+
+#[test]
+fn recursion_depth() {
+    let limits = Limits {
+        depth: Some(50),
+        ..Limits::default()
+    };
+    for mut lazo in sandboxes(limits) {
+        let code = "(define (deep n) (if (= n 0) 0 (+ 1 (deep (- n 1)))))";
+        lazo.eval_str(code).unwrap();
+        assert!(lazo.eval_str("(deep 40)").is_ok());
+        assert_eq!(violation(&mut lazo, "(deep 60)"), "recursion");
+        // Tail calls don't nest
+        let code = "(define (loop n) (if (= n 0) \"done\" (loop (- n 1)))) (loop 1000)";
+        assert!(lazo.eval_str(code).is_ok());
+    }
+}
+
+#[test]
+fn size() {
+    let limits = Limits {
+        size: Some(100),
+        ..Limits::default()
+    };
+    for mut lazo in sandboxes(limits) {
+        assert!(lazo.eval_str("(repeat \"ab\" 50)").is_ok());
+        assert_eq!(violation(&mut lazo, "(repeat \"ab\" 51)"), "size");
+        assert_eq!(violation(&mut lazo, "(reverse (range 1000))"), "size");
+        let code = "(define (grow s) (if (> (len s) 0) (grow (concat s s)))) (grow \"a\")";
+        assert_eq!(violation(&mut lazo, code), "size");
+    }
+}
+
+#[test]
+fn large_numbers() {
+    let limits = Limits {
+        fuel: Some(100000),
+        timeout: Some(Duration::from_millis(200)),
+        size: Some(1000),
+        ..Limits::default()
+    };
+    for mut lazo in sandboxes(limits) {
+        let start = Instant::now();
+        assert!(lazo.eval_str("(^ 2 999)").is_ok());
+        assert_eq!(violation(&mut lazo, "(len (^ 7 30000000))"), "size");
+        assert_eq!(violation(&mut lazo, "(* (^ 2 900) (^ 2 900))"), "size");
+        let code = "(define (grow n) (if (> n 0) (grow (+ n n)))) (grow 1)";
+        assert_eq!(violation(&mut lazo, code), "size");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
+
+#[test]
+fn deep_values() {
+    let limits = Limits {
+        depth: Some(50),
+        size: Some(1000),
+        ..Limits::default()
+    };
+    for mut lazo in sandboxes(limits) {
+        // Thunks that capture the previous one are dropped without the recursion
+        let code =
+            "(define (build n acc) (if (= n 0) acc (build (- n 1) [acc]))) (len (build 200000 []))";
+        assert!(lazo.eval_str(code).is_ok());
+        // Nested lists are counted together
+        let code = "(define (nest n acc) (if (= n 0) acc (let ((next `[,acc])) (len next) (nest (- n 1) next)))) (nest 200000 [])";
+        assert_eq!(violation(&mut lazo, code), "size");
+        // Forcing the thunk is nested like the function call
+        let code = "(define (chain n acc) (if (= n 0) acc (chain (- n 1) (delay (force acc))))) (force (chain 200000 []))";
+        assert_eq!(violation(&mut lazo, code), "recursion");
+    }
+}
+
+#[test]
+fn capabilities() {
+    for mut lazo in sandboxes(Limits::default()) {
+        lazo.deny(Capability::Exit);
+        lazo.deny(Capability::Input);
+        lazo.deny(Capability::Print);
+        lazo.deny(Capability::Io);
+        assert_eq!(violation(&mut lazo, "(exit 1)"), "capability");
+        assert_eq!(violation(&mut lazo, "(input \"> \")"), "capability");
+        assert_eq!(violation(&mut lazo, "(print 1)"), "capability");
+        assert_eq!(violation(&mut lazo, "(debug 1)"), "capability");
+        assert_eq!(violation(&mut lazo, "(import \"module\")"), "capability");
+        assert_eq!(
+            violation(&mut lazo, "(try (exit 1) (catch e 0))"),
+            "capability"
+        );
+        let err = lazo.eval_str("(map [1] print)").unwrap_err();
+        assert!(err.to_string().contains("`print` is not allowed"), "{err}");
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::interpreters;
+
+/// Rendered error of the code, that has the location and the caret under the code
+fn error(code: &str) -> Vec<String> {
+    interpreters()
+        .into_iter()
+        .map(|mut lazo| lazo.eval_str(code).unwrap_err().to_string())
+        .collect()
+}
+
+#[test]
+fn errors_point_at_the_expression() {
+    for err in error("(+ 1 (car 1 2))") {
+        assert_eq!(
+            err,
+            "Arity Error! `car` takes 1 argument, but 2 passed\n --> <string>:1:6\n  |\n1 | (+ 1 (car 1 2))\n  |      ^^^^^^^^^"
+        );
+    }
+    for err in error("(define x 1)\n\n(+ x\n  (car 1 2))") {
+        assert!(err.contains("--> <string>:4:3"), "{err}");
+        assert!(err.ends_with("4 |   (car 1 2))\n  |   ^^^^^^^^^"), "{err}");
+    }
+}
+
+#[test]
+fn syntax_errors_point_at_the_token() {
+    for (code, location) in [
+        ("(print \"abc", "<string>:1:8"),
+        ("(print \"a\\q\")", "<string>:1:8"),
+        ("(+ 1 2))", "<string>:1:8"),
+    ] {
+        for err in error(code) {
+            assert!(err.starts_with("Syntax Error!"), "{err}");
+            assert!(err.contains(location), "{code}: {err}");
+        }
+    }
+}
+
+#[test]
+fn unbound_variables_point_at_the_name() {
+    for err in error("(print (+ 1 undefined-var))") {
+        assert!(
+            err.ends_with("1 | (print (+ 1 undefined-var))\n  |             ^^^^^^^^^^^^^"),
+            "{err}"
+        );
+    }
+    // Argument is evaluated in the function, but it's written at the call
+    for err in error("(define (f x) (+ x 1))\n(f nope)") {
+        assert!(err.contains("--> <string>:2:4"), "{err}");
+    }
+    // Quoted name isn't the variable
+    for err in error("`[(quote zz) ,zz]") {
+        assert!(err.contains("--> <string>:1:15"), "{err}");
+    }
+}
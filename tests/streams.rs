@@ -0,0 +1,136 @@
+mod common;
+
+use common::interpreters;
+use lazo::{Interpreter, Type};
+
+fn numbers(lazo: &mut Interpreter, code: &str) -> Vec<i64> {
+    Vec::try_from(lazo.eval_str(code).unwrap()).unwrap()
+}
+
+fn type_of(lazo: &mut Interpreter, code: &str) -> String {
+    String::try_from(lazo.eval_str(&format!("(type {code})")).unwrap()).unwrap()
+}
+
+#[test]
+fn endless_streams() {
+    for mut lazo in interpreters() {
+        assert_eq!(numbers(&mut lazo, "(take (range) 3)"), [0, 1, 2]);
+        assert_eq!(numbers(&mut lazo, "(take (range 5 null 5) 3)"), [5, 10, 15]);
+        assert_eq!(
+            numbers(&mut lazo, "(take (iterate 1 (lambda (x) (* x 3))) 4)"),
+            [1, 3, 9, 27]
+        );
+        assert_eq!(
+            numbers(&mut lazo, "(take (cycle [1 2]) 5)"),
+            [1, 2, 1, 2, 1]
+        );
+        assert_eq!(numbers(&mut lazo, "(take (drop (range) 10) 2)"), [10, 11]);
+        assert_eq!(
+            numbers(
+                &mut lazo,
+                "(take-while (map (range 1 null) (lambda (x) (* x x))) (lambda (x) (< x 30)))"
+            ),
+            [1, 4, 9, 16, 25]
+        );
+        assert_eq!(
+            numbers(
+                &mut lazo,
+                "(take (filter (range) (lambda (x) (= (% x 7) 0))) 3)"
+            ),
+            [0, 7, 14]
+        );
+        let code =
+            "(= (take (zip (range) (cycle [\"a\" \"b\"])) 3) [[0 \"a\"] [1 \"b\"] [2 \"a\"]])";
+        assert_eq!(lazo.eval_str(code).unwrap(), Type::Bool(true));
+    }
+}
+
+#[test]
+fn elements_are_made_once_when_needed() {
+    for mut lazo in interpreters() {
+        lazo.eval_str(
+            "(define made 0) (define squares (map (range) (lambda (x) (set! made (+ made 1)) (* x x))))",
+        )
+        .unwrap();
+        assert_eq!(i64::try_from(lazo.eval_str("made").unwrap()).unwrap(), 0);
+        assert_eq!(numbers(&mut lazo, "(take squares 4)"), [0, 1, 4, 9]);
+        assert_eq!(numbers(&mut lazo, "(take squares 3)"), [0, 1, 4]);
+        assert_eq!(i64::try_from(lazo.eval_str("made").unwrap()).unwrap(), 4);
+        assert_eq!(
+            i64::try_from(lazo.eval_str("(car (cdr (cdr squares)))").unwrap()).unwrap(),
+            4
+        );
+    }
+}
+
+#[test]
+fn range_is_lazy() {
+    for mut lazo in interpreters() {
+        assert_eq!(
+            numbers(&mut lazo, "(take (range 1 1000000000) 3)"),
+            [1, 2, 3]
+        );
+        // Bounds are evaluated only once
+        let code =
+            "(define calls 0) (define (end) (set! calls (+ calls 1)) 5) (len (range (end))) calls";
+        assert_eq!(i64::try_from(lazo.eval_str(code).unwrap()).unwrap(), 1);
+        assert_eq!(numbers(&mut lazo, "(range 0 10 3)"), [0, 3, 6, 9]);
+        for code in ["(range 0 3 0)", "(range 0 null 0.0)"] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert!(err.to_string().contains("should not be zero"), "{err}");
+        }
+        assert_eq!(type_of(&mut lazo, "(range 3)"), "stream");
+        assert_eq!(
+            lazo.eval_str("(= (range 3) [0 1 2])").unwrap(),
+            Type::Bool(true)
+        );
+        let code =
+            "(define total 0) (for (range 100001) (lambda (i) (set! total (+ total i)))) total";
+        assert_eq!(
+            i64::try_from(lazo.eval_str(code).unwrap()).unwrap(),
+            5000050000
+        );
+    }
+}
+
+#[test]
+fn lists_stay_lists() {
+    for mut lazo in interpreters() {
+        assert_eq!(type_of(&mut lazo, "(take [1 2 3] 2)"), "list");
+        assert_eq!(type_of(&mut lazo, "(zip [1 2] [3 4])"), "list");
+        assert_eq!(type_of(&mut lazo, "(map [1 2] (lambda (x) x))"), "list");
+        assert_eq!(
+            type_of(&mut lazo, "(map (range 2) (lambda (x) x))"),
+            "stream"
+        );
+        assert_eq!(numbers(&mut lazo, "(drop [1 2 3] 1)"), [2, 3]);
+        // Stream is also accepted as the list
+        let code = "(define (sum (xs : list)) (reduce xs +)) (sum (range 5))";
+        assert_eq!(i64::try_from(lazo.eval_str(code).unwrap()).unwrap(), 10);
+    }
+}
+
+#[test]
+fn unused_argument_does_not_walk_stream() {
+    for mut lazo in interpreters() {
+        let code = "(define (f x) 1) (define s (filter (range) (lambda (x) false))) (type s) `[,(f (car s)) ,(f (= s s))]";
+        assert_eq!(format!("{:?}", lazo.eval_str(code).unwrap()), "[1 1]");
+    }
+}
+
+#[test]
+fn streams_are_compared_without_realizing() {
+    for mut lazo in interpreters() {
+        let code = "(define s (range)) `[,(= s s) ,(= (range) (range)) ,(!= (range) (range)) ,(equal? s s)]";
+        assert_eq!(
+            format!("{:?}", lazo.eval_str(code).unwrap()),
+            "[true false true true]"
+        );
+        // Stream is read only as long as the list
+        let code = "`[,(= (range) [0 1]) ,(= [0 1 2] (range 3)) ,(= (range 3) [0 1])]";
+        assert_eq!(
+            format!("{:?}", lazo.eval_str(code).unwrap()),
+            "[false true false]"
+        );
+    }
+}
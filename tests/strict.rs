@@ -0,0 +1,46 @@
+mod common;
+
+use common::{interpreters, number, text};
+
+#[test]
+fn values_are_converted_by_default() {
+    for mut lazo in interpreters() {
+        assert_eq!(number(&mut lazo, "(+ \"1\" 2)"), 3);
+        assert_eq!(text(&mut lazo, "(concat 1 2)"), "12");
+        assert_eq!(number(&mut lazo, "(car 5)"), 5);
+    }
+}
+
+#[test]
+fn strict_pragma_raises_type_errors() {
+    for mut lazo in interpreters() {
+        // Pragma takes effect on the rest of the function where it's declared
+        lazo.eval_str("(define (f) (strict) (+ \"1\" 2)) (define (g) (+ \"1\" 2))")
+            .unwrap();
+        let err = lazo.eval_str("(f)").unwrap_err();
+        assert_eq!(err.kind(), "type");
+        assert!(
+            err.to_string()
+                .starts_with("Type Error! expected number, got string `\"1\"`"),
+            "{err}"
+        );
+        assert_eq!(number(&mut lazo, "(g)"), 3);
+        let err = lazo.eval_str("(strict) (car 5)").unwrap_err();
+        assert_eq!(err.kind(), "type");
+    }
+}
+
+#[test]
+fn strict_mode_of_the_host() {
+    for mut lazo in interpreters() {
+        lazo.set_strict(true);
+        for code in ["(+ \"1\" 2)", "(car 5)", "(len 5)"] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "type", "{code}");
+        }
+        // Values of the right types work as they are
+        assert_eq!(number(&mut lazo, "(+ 1 2)"), 3);
+        lazo.set_strict(false);
+        assert_eq!(number(&mut lazo, "(+ \"1\" 2)"), 3);
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{interpreters, text};
+use lazo::Type;
+
+#[test]
+fn escape_sequences() {
+    for mut lazo in interpreters() {
+        assert_eq!(text(&mut lazo, r#""a\tb\nc\r""#), "a\tb\nc\r");
+        assert_eq!(text(&mut lazo, r#""\"q\" \\ \' \0""#), "\"q\" \\ ' \0");
+        assert_eq!(
+            text(&mut lazo, r#""\u{3042}\u{1F600}""#),
+            "\u{3042}\u{1F600}"
+        );
+        // Escaped quote doesn't close the string
+        assert_eq!(text(&mut lazo, r#"(concat "a\"" "b")"#), "a\"b");
+    }
+}
+
+#[test]
+fn malformed_escapes() {
+    for mut lazo in interpreters() {
+        for (code, message) in [
+            (r#""\x""#, "unknown escape sequence `\\x`"),
+            (r#""\u{zz}""#, "invalid unicode escape"),
+            (r#""\u{110000}""#, "invalid unicode escape"),
+        ] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "syntax", "{code}");
+            assert!(err.to_string().contains(message), "{err}");
+            assert!(err.to_string().contains("<string>:1:1"), "{err}");
+        }
+    }
+}
+
+#[test]
+fn raw_strings() {
+    for mut lazo in interpreters() {
+        assert_eq!(text(&mut lazo, r#"r"a\nb\t""#), "a\\nb\\t");
+        assert_eq!(text(&mut lazo, r#"r"C:\path\to""#), "C:\\path\\to");
+        assert_eq!(
+            lazo.eval_str(r#"(= r"a\nb" "a\\nb")"#).unwrap(),
+            Type::Bool(true)
+        );
+    }
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::interpreters;
+
+/// Name that the unbound variable error suggests
+fn suggestion(code: &str) -> Vec<Option<String>> {
+    interpreters()
+        .into_iter()
+        .map(|mut lazo| {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "unbound-variable");
+            let message = err.message();
+            message
+                .split_once(", did you mean `")
+                .map(|(_, rest)| rest.trim_end_matches("`?").to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn similar_names_are_suggested() {
+    for (code, expected) in [
+        ("(prnt 1)", "print"),
+        ("(lenn [1])", "len"),
+        ("(define my-value 1) my-valeu", "my-value"),
+        ("(let ((alpha 1)) alph)", "alpha"),
+        ("(define (f total) (+ totl 1)) (f 1)", "total"),
+    ] {
+        for found in suggestion(code) {
+            assert_eq!(found.as_deref(), Some(expected), "{code}");
+        }
+    }
+}
+
+#[test]
+fn nothing_is_suggested_for_different_names() {
+    for code in ["(xyzzyq 1)", "(ab 1)"] {
+        for found in suggestion(code) {
+            assert_eq!(found, None, "{code}");
+        }
+    }
+}
+
+#[test]
+fn renamed_variables_of_macros_are_hidden() {
+    // Definition that the macro introduces is renamed, so it's not visible by its name
+    let code = "(define-syntax def-helper (syntax-rules () ((_ v) (define helper-value v)))) (def-helper 1) helper-value";
+    for found in suggestion(code) {
+        assert_eq!(found, None);
+    }
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::{interpreters, number};
+
+#[test]
+fn self_recursion_runs_in_constant_stack() {
+    for mut lazo in interpreters() {
+        let code = "(define (loop n acc) (if (= n 0) acc (loop (- n 1) (+ acc 1)))) (loop 20000 0)";
+        assert_eq!(number(&mut lazo, code), 20000);
+        let code = "(define (down n) (cond ((= n 0) 0) (else (down (- n 1))))) (down 20000)";
+        assert_eq!(number(&mut lazo, code), 0);
+        let code =
+            "(define (count n) (let ((m (- n 1))) (if (= m 0) 0 (begin (count m))))) (count 20000)";
+        assert_eq!(number(&mut lazo, code), 0);
+    }
+}
+
+#[test]
+fn mutual_recursion_runs_in_constant_stack() {
+    for mut lazo in interpreters() {
+        let code = "(define (ev n) (if (= n 0) true (od (- n 1)))) (define (od n) (if (= n 0) false (ev (- n 1)))) (ev 20001)";
+        assert!(!bool::try_from(lazo.eval_str(code).unwrap()).unwrap());
+        // Call through the lambda is also in tail position
+        let code = "(define (spin f n) (if (= n 0) 7 (f f (- n 1)))) (spin spin 20000)";
+        assert_eq!(number(&mut lazo, code), 7);
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{interpreters, number};
+
+#[test]
+fn static_type_errors() {
+    for mut lazo in interpreters() {
+        for code in [
+            "(define (f (x : number)) x) (f \"s\")",
+            "(define (g) -> number \"s\")",
+            "(the string 1)",
+        ] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "type", "{code}");
+        }
+        // Nothing runs when the check fails
+        let code = "(define ran false) (set! ran true) (define (f (x : number)) x) (f \"s\")";
+        assert!(lazo.eval_str(code).is_err());
+        assert!(lazo.eval_str("ran").is_err());
+    }
+}
+
+#[test]
+fn unknown_type_names() {
+    for mut lazo in interpreters() {
+        for (code, name) in [
+            ("(define (h (x : nubmer)) x) (h 1)", "nubmer"),
+            ("(define (h x) -> strng x)", "strng"),
+            ("(the strng \"s\")", "strng"),
+            ("(define (h [a (b : nmber)]) a)", "nmber"),
+            ("(define (k v) (match v ((p : pont) 1) (_ 2)))", "pont"),
+        ] {
+            let err = lazo.eval_str(code).unwrap_err();
+            assert_eq!(err.kind(), "syntax", "{code}");
+            assert!(
+                err.to_string()
+                    .contains(&format!("unknown type name `{name}`")),
+                "{err}"
+            );
+        }
+        // Record type is known after it's defined, or if it's defined later in the program
+        let code = "(define (f (p : point)) 1) (define-record point (x)) (f (make-point 1))";
+        assert_eq!(number(&mut lazo, code), 1);
+        assert_eq!(
+            number(&mut lazo, "(define (g (p : point)) 2) (g (make-point 1))"),
+            2
+        );
+    }
+}
+
+#[test]
+fn runtime_checks_from_untyped_code() {
+    for mut lazo in interpreters() {
+        lazo.eval_str("(define (f (x : number)) (+ x 1)) (define (g y) (f y))")
+            .unwrap();
+        assert_eq!(number(&mut lazo, "(g 1)"), 2);
+        let err = lazo.eval_str("(g \"s\")").unwrap_err();
+        assert_eq!(err.kind(), "type");
+        assert!(err.to_string().contains("expected number"), "{err}");
+        let code = "(define (k y) -> number y) (k \"s\")";
+        assert_eq!(lazo.eval_str(code).unwrap_err().kind(), "type");
+    }
+}
+
+#[test]
+fn unused_argument_is_not_checked() {
+    for mut lazo in interpreters() {
+        // Checking the argument is deferred until it's evaluated, like the argument itself
+        let code = "(define (f (x : number)) 1) (define (g y) (f y)) (g \"s\")";
+        assert_eq!(number(&mut lazo, code), 1);
+        let code = "(define (h (x : number)) 1) (define (k y) (h y)) (k (error \"never\"))";
+        assert_eq!(number(&mut lazo, code), 1);
+    }
+}
+
+#[test]
+fn return_type_error_points_at_the_body() {
+    for mut lazo in interpreters() {
+        for (code, location) in [
+            ("(define (g) -> number \"s\")", "<string>:1:23"),
+            ("(define (g) -> number 1 \"s\")", "<string>:1:25"),
+            ("(define g (lambda () -> number \"s\"))", "<string>:1:32"),
+            (
+                "(define (g) -> number (concat \"a\" \"b\"))",
+                "<string>:1:23",
+            ),
+        ] {
+            let err = lazo.eval_str(code).unwrap_err().to_string();
+            assert!(err.contains(location), "{err}");
+        }
+    }
+}